@@ -1,24 +1,101 @@
-use std::{path::Path, process::Command};
+use std::{
+    collections::VecDeque,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus, Stdio},
+    sync::mpsc,
+    thread,
+};
+use tempfile::TempDir;
 use thiserror::Error;
 
-use crate::{build::package::package::Build, handlers::events::InstallEvent};
+use crate::{
+    build::{
+        build_step_handler::{
+            build_log::TAIL_LINES,
+            environment::BuildEnvironment,
+            sandbox::{CACHE_VARS, Sandbox},
+        },
+        package::package::{Build, BuildPhase},
+    },
+    handlers::events::InstallEvent,
+};
 
 #[derive(Debug, Error)]
 pub enum BuildHandlerError {
-    #[error("Build step failed: {0}")]
-    BuildStepFailed(String),
+    #[error("Build step failed: {step}{}", format_failure(.tail, .log.as_deref()))]
+    BuildStepFailed {
+        step: String,
+        tail: Vec<String>,
+        log: Option<PathBuf>,
+    },
 
     #[error("Failed to spawn process: {0}")]
     SpawnError(#[from] std::io::Error),
+
+    #[error("Failed to write build log: {0}")]
+    LogError(std::io::Error),
+}
+
+fn format_failure(tail: &[String], log: Option<&Path>) -> String {
+    let mut message: String = tail.iter().map(|line| format!("\n  | {line}")).collect();
+    if let Some(log) = log {
+        message.push_str(&format!("\nfull log: {}", log.display()));
+    }
+    message
 }
 
 pub struct BuildHandler {
     pub build_steps: Build,
+    destdir: Option<PathBuf>,
+    sandbox: Option<Sandbox>,
+    environment: BuildEnvironment,
+    log: Option<PathBuf>,
+    checks: bool,
 }
 
 impl BuildHandler {
     pub fn new(build: Build) -> Self {
-        BuildHandler { build_steps: build }
+        BuildHandler {
+            build_steps: build,
+            destdir: None,
+            sandbox: None,
+            environment: BuildEnvironment::new::<&str>(&[]),
+            log: None,
+            checks: false,
+        }
+    }
+
+    pub fn with_destdir<P: Into<PathBuf>>(mut self, destdir: P) -> Self {
+        self.destdir = Some(destdir.into());
+        self
+    }
+
+    /// Replaces the default environment of allowlisted variables.
+    pub fn with_environment(mut self, environment: BuildEnvironment) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// Writes the output of every step to `log`, replacing its contents.
+    pub fn with_log<P: Into<PathBuf>>(mut self, log: P) -> Self {
+        self.log = Some(log.into());
+        self
+    }
+
+    /// Runs the check phase, which is skipped by default.
+    pub fn with_checks(mut self, checks: bool) -> Self {
+        self.checks = checks;
+        self
+    }
+
+    /// Runs the steps in `sandbox`, where only the source dir, DESTDIR and a
+    /// scratch TMPDIR are writable. Tool caches such as CARGO_HOME move into
+    /// the scratch dir.
+    pub fn with_sandbox(mut self, sandbox: Sandbox) -> Self {
+        self.sandbox = Some(sandbox);
+        self
     }
 
     pub fn run_build_steps<F: FnMut(InstallEvent)>(
@@ -26,19 +103,142 @@ impl BuildHandler {
         path: &Path,
         progress: &mut F,
     ) -> Result<(), BuildHandlerError> {
-        for step in &self.build_steps.steps {
-            progress(InstallEvent::BuildStep { step: step.clone() });
-            let status = Command::new("sh")
-                .arg("-c")
-                .arg(step)
-                .current_dir(path)
-                .status()
-                .map_err(BuildHandlerError::SpawnError)?;
-
-            if !status.success() {
-                return Err(BuildHandlerError::BuildStepFailed(step.to_string()));
+        let scratch = TempDir::new()?;
+        let sandbox = self.sandbox.clone().map(|sandbox| {
+            let sandbox = sandbox.with_writable(path).with_writable(scratch.path());
+            match &self.destdir {
+                Some(destdir) => sandbox.with_writable(destdir),
+                None => sandbox,
+            }
+        });
+
+        let mut environment = self
+            .environment
+            .clone()
+            .with_var("SRCDIR", path.to_string_lossy())
+            .with_var("TMPDIR", scratch.path().to_string_lossy());
+        if let Some(destdir) = &self.destdir {
+            environment = environment.with_var("DESTDIR", destdir.to_string_lossy());
+        }
+        if sandbox.is_some() {
+            for name in CACHE_VARS {
+                let cache = scratch.path().join("cache").join(name.to_ascii_lowercase());
+                environment = environment.with_var(*name, cache.to_string_lossy());
+            }
+        }
+        let vars = environment.resolve(&self.build_steps.env);
+
+        if let Some(sandbox) = &sandbox {
+            progress(InstallEvent::Sandboxed {
+                tool: sandbox.tool().name().to_string(),
+                network: sandbox.network(),
+            });
+        }
+
+        let mut log = self.open_log().map_err(BuildHandlerError::LogError)?;
+        let mut tail = VecDeque::with_capacity(TAIL_LINES);
+
+        for (phase, steps) in self.build_steps.phases() {
+            if steps.is_empty() || (phase == BuildPhase::Check && !self.checks) {
+                continue;
+            }
+            progress(InstallEvent::BuildPhase {
+                phase: phase.name().to_string(),
+            });
+            if let Some(log) = &mut log {
+                writeln!(log, "==> {} phase", phase.name()).map_err(BuildHandlerError::LogError)?;
+            }
+
+            for step in &steps {
+                progress(InstallEvent::BuildStep { step: step.clone() });
+                if let Some(log) = &mut log {
+                    writeln!(log, "-> {step}").map_err(BuildHandlerError::LogError)?;
+                }
+                let mut command = match &sandbox {
+                    Some(sandbox) => sandbox.command(step)?,
+                    None => {
+                        let mut command = Command::new("sh");
+                        command.arg("-c").arg(step);
+                        command
+                    }
+                };
+                command.current_dir(path).env_clear().envs(&vars);
+
+                tail.clear();
+                let status = stream_output(command, &mut |line| {
+                    if let Some(log) = &mut log {
+                        writeln!(log, "{line}").map_err(BuildHandlerError::LogError)?;
+                    }
+                    if tail.len() == TAIL_LINES {
+                        tail.pop_front();
+                    }
+                    tail.push_back(line.clone());
+                    progress(InstallEvent::BuildOutput { line });
+                    Ok(())
+                })?;
+
+                if !status.success() {
+                    return Err(BuildHandlerError::BuildStepFailed {
+                        step: step.to_string(),
+                        tail: tail.into(),
+                        log: self.log.clone(),
+                    });
+                }
             }
         }
         Ok(())
     }
+
+    fn open_log(&self) -> io::Result<Option<File>> {
+        let Some(path) = &self.log else {
+            return Ok(None);
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        File::create(path).map(Some)
+    }
+}
+
+/// Runs `command`, passing each line of stdout and stderr to `on_line` as
+/// it arrives.
+fn stream_output<F>(mut command: Command, on_line: &mut F) -> Result<ExitStatus, BuildHandlerError>
+where
+    F: FnMut(String) -> Result<(), BuildHandlerError>,
+{
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(BuildHandlerError::SpawnError)?;
+
+    let (sender, receiver) = mpsc::channel();
+    let stdout = child
+        .stdout
+        .take()
+        .map(|out| Box::new(out) as Box<dyn Read + Send>);
+    let stderr = child
+        .stderr
+        .take()
+        .map(|err| Box::new(err) as Box<dyn Read + Send>);
+    for output in [stdout, stderr].into_iter().flatten() {
+        let sender = sender.clone();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(output);
+            let mut buf = Vec::new();
+            while reader.read_until(b'\n', &mut buf).is_ok_and(|n| n > 0) {
+                let line = String::from_utf8_lossy(&buf);
+                let _ = sender.send(line.trim_end_matches(['\n', '\r']).to_string());
+                buf.clear();
+            }
+        });
+    }
+    drop(sender);
+
+    if let Err(e) = receiver.into_iter().try_for_each(on_line) {
+        let _ = child.kill();
+        let _ = child.wait();
+        return Err(e);
+    }
+    child.wait().map_err(BuildHandlerError::SpawnError)
 }
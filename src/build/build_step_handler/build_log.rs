@@ -0,0 +1,69 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Build logs kept per package; older ones are removed after each install.
+pub const LOG_LIMIT: usize = 5;
+
+/// Output lines included in the error when a step fails.
+pub const TAIL_LINES: usize = 20;
+
+/// Build logs stored as `<dir>/<package>/<millis>-<version>.log`.
+pub struct BuildLogs {
+    dir: PathBuf,
+}
+
+impl BuildLogs {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        BuildLogs { dir: dir.into() }
+    }
+
+    /// A fresh path for a build of `name@version` started now.
+    pub fn next_path(&self, name: &str, version: &str) -> PathBuf {
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        self.dir.join(name).join(format!("{millis}-{version}.log"))
+    }
+
+    /// Logs of `name`, oldest first.
+    pub fn logs(&self, name: &str) -> io::Result<Vec<PathBuf>> {
+        let dir = self.dir.join(name);
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut logs: Vec<(u128, PathBuf)> = fs::read_dir(dir)?
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                Some((started_at(&path)?, path))
+            })
+            .collect();
+        logs.sort();
+        Ok(logs.into_iter().map(|(_, path)| path).collect())
+    }
+
+    pub fn latest(&self, name: &str) -> io::Result<Option<PathBuf>> {
+        Ok(self.logs(name)?.pop())
+    }
+
+    /// Removes all but the `keep` most recent logs of `name`.
+    pub fn prune(&self, name: &str, keep: usize) -> io::Result<()> {
+        let logs = self.logs(name)?;
+        for path in &logs[..logs.len().saturating_sub(keep)] {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+fn started_at(path: &Path) -> Option<u128> {
+    if path.extension()? != "log" {
+        return None;
+    }
+    let stem = path.file_stem()?.to_str()?;
+    stem.split_once('-')?.0.parse().ok()
+}
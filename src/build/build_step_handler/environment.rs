@@ -0,0 +1,89 @@
+use std::{collections::BTreeMap, env};
+
+/// Variables passed through from the caller's environment; everything else
+/// is cleared so builds behave the same for everyone.
+pub const PASSTHROUGH: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "SHELL",
+    "TERM",
+    "LANG",
+    "LC_ALL",
+    "TZ",
+    "SSL_CERT_FILE",
+    "SSL_CERT_DIR",
+    "http_proxy",
+    "https_proxy",
+    "no_proxy",
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "NO_PROXY",
+    "CARGO_HOME",
+    "RUSTUP_HOME",
+];
+
+/// Variables Reponere sets for every build; recipes and config cannot
+/// override them.
+pub const PROVIDED: &[&str] = &[
+    "PREFIX",
+    "DESTDIR",
+    "SRCDIR",
+    "JOBS",
+    "PKG_NAME",
+    "PKG_VERSION",
+    "TMPDIR",
+];
+
+/// The environment build steps run in. Later layers win: passed-through
+/// variables, then the recipe's `env`, then the user's overrides, then the
+/// variables Reponere provides.
+#[derive(Debug, Clone, Default)]
+pub struct BuildEnvironment {
+    passthrough: BTreeMap<String, String>,
+    overrides: BTreeMap<String, String>,
+    provided: BTreeMap<String, String>,
+}
+
+impl BuildEnvironment {
+    /// Starts from the allowlisted variables of the current process, plus
+    /// any `extra` names.
+    pub fn new<S: AsRef<str>>(extra: &[S]) -> Self {
+        let passthrough = PASSTHROUGH
+            .iter()
+            .copied()
+            .chain(extra.iter().map(AsRef::as_ref))
+            .filter_map(|name| Some((name.to_string(), env::var(name).ok()?)))
+            .collect();
+
+        BuildEnvironment {
+            passthrough,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_overrides(mut self, overrides: &BTreeMap<String, String>) -> Self {
+        self.overrides.extend(overrides.clone());
+        self
+    }
+
+    pub fn with_var<K: Into<String>, V: Into<String>>(mut self, name: K, value: V) -> Self {
+        self.provided.insert(name.into(), value.into());
+        self
+    }
+
+    /// The final variables for a recipe declaring `recipe_env`.
+    pub fn resolve(&self, recipe_env: &BTreeMap<String, String>) -> BTreeMap<String, String> {
+        let mut vars = self.passthrough.clone();
+        vars.extend(recipe_env.clone());
+        vars.extend(
+            self.overrides
+                .iter()
+                .filter(|(name, _)| !PROVIDED.contains(&name.as_str()))
+                .map(|(name, value)| (name.clone(), value.clone())),
+        );
+        vars.extend(self.provided.clone());
+        vars
+    }
+}
@@ -1 +1,4 @@
 pub mod build_handler;
+pub mod build_log;
+pub mod environment;
+pub mod sandbox;
@@ -0,0 +1,160 @@
+use std::{
+    io,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+/// Runs inside fresh user and mount namespaces: binds every writable path
+/// onto itself, then remounts everything else read-only and runs the step.
+/// A mount that cannot be made read-only aborts the step.
+const UNSHARE_SCRIPT: &str = r#"set -e
+step="$1"; shift
+for w in "$@"; do mount --bind "$w" "$w"; done
+while read -r _ _ _ _ point _; do
+  case "$point" in /proc|/proc/*|/sys|/sys/*|/dev|/dev/*) continue ;; esac
+  skip=
+  for w in "$@"; do [ "$point" = "$w" ] && skip=1; done
+  [ -n "$skip" ] || mount -o remount,bind,ro "$point"
+done < /proc/self/mountinfo
+# the working directory still refers to the mount from before the binds
+cd "$(pwd)"
+exec sh -c "$step"
+"#;
+
+/// Variables naming the download caches of build tools. The home directory
+/// is read-only in the sandbox, so they point into the writable scratch dir.
+pub const CACHE_VARS: &[&str] = &[
+    "CARGO_HOME",
+    "GOPATH",
+    "GOCACHE",
+    "GOMODCACHE",
+    "PIP_CACHE_DIR",
+    "npm_config_cache",
+    "XDG_CACHE_HOME",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SandboxTool {
+    Bubblewrap,
+    Unshare,
+}
+
+impl SandboxTool {
+    /// Finds a tool that can create the namespaces on this system,
+    /// preferring bubblewrap.
+    pub fn detect() -> Option<SandboxTool> {
+        let works = |command: &mut Command| {
+            command
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .is_ok_and(|status| status.success())
+        };
+
+        if works(Command::new("bwrap").args(["--ro-bind", "/", "/", "--unshare-user", "true"])) {
+            Some(SandboxTool::Bubblewrap)
+        } else if works(Command::new("unshare").args([
+            "--user",
+            "--map-root-user",
+            "--mount",
+            "--net",
+            "true",
+        ])) {
+            Some(SandboxTool::Unshare)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SandboxTool::Bubblewrap => "bubblewrap",
+            SandboxTool::Unshare => "unshare",
+        }
+    }
+}
+
+/// Confines build steps to a set of writable paths; the rest of the
+/// filesystem, including the home directory, is read-only. Network access
+/// is cut off unless enabled.
+#[derive(Debug, Clone)]
+pub struct Sandbox {
+    tool: SandboxTool,
+    writable: Vec<PathBuf>,
+    network: bool,
+}
+
+impl Sandbox {
+    pub fn new(tool: SandboxTool) -> Self {
+        Sandbox {
+            tool,
+            writable: Vec::new(),
+            network: false,
+        }
+    }
+
+    pub fn with_writable<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.writable.push(path.into());
+        self
+    }
+
+    pub fn with_network(mut self, network: bool) -> Self {
+        self.network = network;
+        self
+    }
+
+    pub fn tool(&self) -> SandboxTool {
+        self.tool
+    }
+
+    pub fn network(&self) -> bool {
+        self.network
+    }
+
+    /// Builds the command that runs `step` with `sh -c` inside the sandbox.
+    pub fn command(&self, step: &str) -> io::Result<Command> {
+        // mounts are matched by their real path, not through symlinks
+        let writable = self
+            .writable
+            .iter()
+            .map(|path| path.canonicalize())
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let command = match self.tool {
+            SandboxTool::Bubblewrap => {
+                let mut command = Command::new("bwrap");
+                command.args([
+                    "--ro-bind",
+                    "/",
+                    "/",
+                    "--dev",
+                    "/dev",
+                    "--proc",
+                    "/proc",
+                    "--unshare-user",
+                    "--unshare-pid",
+                    "--die-with-parent",
+                ]);
+                if !self.network {
+                    command.arg("--unshare-net");
+                }
+                for path in &writable {
+                    command.arg("--bind").arg(path).arg(path);
+                }
+                command.args(["sh", "-c", step]);
+                command
+            }
+            SandboxTool::Unshare => {
+                let mut command = Command::new("unshare");
+                command.args(["--user", "--map-root-user", "--mount"]);
+                if !self.network {
+                    command.arg("--net");
+                }
+                command.args(["sh", "-c", UNSHARE_SCRIPT, "sh", step]);
+                command.args(&writable);
+                command
+            }
+        };
+        Ok(command)
+    }
+}
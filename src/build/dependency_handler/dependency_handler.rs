@@ -1,10 +1,11 @@
 use std::fmt;
 
-use crate::build::dependency_handler::version::VersionRequirement;
+use crate::build::dependency_handler::version::{VersionError, VersionRequirement};
 use crate::build::package::package::{Dependencies, Dependency};
 use crate::build::package_manager::manager::{PackageManagerApi, PackageManagerError};
 use crate::handlers::events::InstallEvent;
 
+#[allow(clippy::enum_variant_names)]
 pub enum DependencyError {
     InstallFailed {
         dependency: String,
@@ -18,6 +19,14 @@ pub enum DependencyError {
         dependency: String,
         source: PackageManagerError,
     },
+    InvalidVersionRequirement {
+        dependency: String,
+        source: VersionError,
+    },
+    InvalidVersion {
+        dependency: String,
+        source: VersionError,
+    },
 }
 
 impl fmt::Display for DependencyError {
@@ -38,6 +47,12 @@ impl fmt::Display for DependencyError {
                     "Failed to check available versions of {dependency}: {source}"
                 )
             }
+            DependencyError::InvalidVersionRequirement { dependency, source } => {
+                write!(f, "Invalid version requirement for {dependency}: {source}")
+            }
+            DependencyError::InvalidVersion { dependency, source } => {
+                write!(f, "Cannot compare versions of {dependency}: {source}")
+            }
         }
     }
 }
@@ -110,9 +125,14 @@ impl<'a, PM: PackageManagerApi> DependencyHandler<'a, PM> {
         errors: &mut Vec<DependencyError>,
     ) -> bool {
         match self.package_manager.get_installed_version(&dependency.name) {
-            Ok(Some(installed_version)) => {
-                !self.check_version(&dependency.version_req, &installed_version)
-            }
+            Ok(Some(installed_version)) => match self.check_version(dependency, &installed_version)
+            {
+                Ok(satisfied) => !satisfied,
+                Err(e) => {
+                    errors.push(e);
+                    false
+                }
+            },
             Ok(None) => self.check_not_installed_availability(dependency, errors),
             Err(e) => {
                 errors.push(DependencyError::InstalledVersionCheckFailed {
@@ -124,13 +144,23 @@ impl<'a, PM: PackageManagerApi> DependencyHandler<'a, PM> {
         }
     }
 
-    fn check_version(&self, version_requirement: &Option<String>, installed_version: &str) -> bool {
-        match version_requirement {
-            Some(req) => {
-                let requirement = VersionRequirement::parse_requirement(req);
-                requirement.matches(installed_version)
-            }
-            None => true,
+    fn check_version(
+        &self,
+        dependency: &Dependency,
+        version: &str,
+    ) -> Result<bool, DependencyError> {
+        match &dependency.version_req {
+            Some(req) => VersionRequirement::parse_requirement(req)
+                .map_err(|e| DependencyError::InvalidVersionRequirement {
+                    dependency: dependency.name.clone(),
+                    source: e,
+                })?
+                .matches(version)
+                .map_err(|e| DependencyError::InvalidVersion {
+                    dependency: dependency.name.clone(),
+                    source: e,
+                }),
+            None => Ok(true),
         }
     }
 
@@ -141,7 +171,13 @@ impl<'a, PM: PackageManagerApi> DependencyHandler<'a, PM> {
     ) -> bool {
         match self.package_manager.get_available_version(&dependency.name) {
             Ok(Some(available_version)) => {
-                self.check_version(&dependency.version_req, &available_version)
+                match self.check_version(dependency, &available_version) {
+                    Ok(satisfied) => satisfied,
+                    Err(e) => {
+                        errors.push(e);
+                        false
+                    }
+                }
             }
             Ok(None) => {
                 errors.push(DependencyError::AvailableVersionCheckFailed {
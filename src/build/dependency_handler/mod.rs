@@ -1,3 +1,5 @@
 pub mod build_dependency_guard;
+#[allow(clippy::module_inception)]
 pub mod dependency_handler;
+pub mod reponere_resolver;
 pub mod version;
@@ -0,0 +1,444 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+};
+
+use thiserror::Error;
+
+use crate::build::{
+    dependency_handler::version::{Version, VersionError, VersionRequirement},
+    package::{
+        package::{Dependency, DependencySource, Package},
+        parse::PackageParser,
+    },
+    package_tracker::package_tracker::PackageTracker,
+    registry::registry_handler::Registry,
+};
+
+#[derive(Debug, Error)]
+pub enum ResolveError {
+    #[error("{0} is not in the registry")]
+    NotInRegistry(String),
+
+    #[error("dependency cycle: {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+
+    #[error("no consistent set of releases found:\n{}", .0.join("\n"))]
+    NoSolution(Vec<String>),
+
+    #[error("cannot compare versions of {name}: {source}")]
+    InvalidVersion { name: String, source: VersionError },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedPackage {
+    pub name: String,
+    pub version: String,
+}
+
+pub fn is_reponere_dependency(dependency: &Dependency, registry: &Registry) -> bool {
+    match dependency.source {
+        Some(DependencySource::Reponere) => true,
+        Some(DependencySource::System) => false,
+        None => registry.get_package(&dependency.name).is_some(),
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Constraint {
+    version_req: Option<String>,
+    required_by: String,
+}
+
+impl Constraint {
+    /// A requirement that does not parse allows nothing; `describe` shows
+    /// why.
+    fn allows(&self, version: &str) -> Result<bool, VersionError> {
+        match self
+            .version_req
+            .as_deref()
+            .map(VersionRequirement::parse_requirement)
+        {
+            Some(Ok(requirement)) => requirement.matches(version),
+            Some(Err(_)) => Ok(false),
+            None => Ok(true),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self
+            .version_req
+            .as_deref()
+            .map(|req| (req, VersionRequirement::parse_requirement(req)))
+        {
+            Some((_, Err(e))) => format!("{e} (required by {})", self.required_by),
+            Some((req, Ok(_))) => format!("{req} (required by {})", self.required_by),
+            None => format!("any version (required by {})", self.required_by),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct SearchState {
+    selected: BTreeMap<String, String>,
+    constraints: HashMap<String, Vec<Constraint>>,
+    edges: HashMap<String, Vec<String>>,
+    pending: Vec<String>,
+}
+
+impl SearchState {
+    fn require(&mut self, name: &str, dependency: &Dependency, required_by: &str) {
+        self.constraints
+            .entry(dependency.name.clone())
+            .or_default()
+            .push(Constraint {
+                version_req: dependency.version_req.clone(),
+                required_by: required_by.to_string(),
+            });
+        self.edges
+            .entry(name.to_string())
+            .or_default()
+            .push(dependency.name.clone());
+
+        if !self.selected.contains_key(&dependency.name) && !self.pending.contains(&dependency.name)
+        {
+            self.pending.push(dependency.name.clone());
+        }
+    }
+
+    fn constraints_on(&self, name: &str) -> &[Constraint] {
+        self.constraints.get(name).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    fn allows(&self, name: &str, version: &str) -> Result<bool, ResolveError> {
+        for constraint in self.constraints_on(name) {
+            let allowed =
+                constraint
+                    .allows(version)
+                    .map_err(|source| ResolveError::InvalidVersion {
+                        name: name.to_string(),
+                        source,
+                    })?;
+            if !allowed {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+pub struct ReponereResolver<'a> {
+    registry: &'a Registry,
+    installed: Option<&'a PackageTracker>,
+    removing: &'a [String],
+    recipes: RefCell<HashMap<(String, String), Result<Package, String>>>,
+}
+
+impl<'a> ReponereResolver<'a> {
+    pub fn new(registry: &'a Registry) -> Self {
+        ReponereResolver {
+            registry,
+            installed: None,
+            removing: &[],
+            recipes: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Takes installed packages into account: their versions are preferred
+    /// and the requirements they place on their dependencies must still hold.
+    pub fn with_installed(mut self, tracker: &'a PackageTracker) -> Self {
+        self.installed = Some(tracker);
+        self
+    }
+
+    /// Installed packages that are about to be removed; their requirements
+    /// no longer have to hold.
+    pub fn with_removing(mut self, names: &'a [String]) -> Self {
+        self.removing = names;
+        self
+    }
+
+    /// Returns the Reponere-managed dependencies of `package`, transitively,
+    /// ordered so that every package comes after the packages it depends on.
+    pub fn resolve(&self, package: &Package) -> Result<Vec<ResolvedPackage>, ResolveError> {
+        let root = format!("{}@{}", package.name, package.version);
+
+        let mut state = self.initial_state(&[package.name.as_str()]);
+        state
+            .selected
+            .insert(package.name.clone(), package.version.clone());
+        for dependency in self.reponere_dependencies(package) {
+            state.require(&package.name, dependency, &root);
+        }
+
+        let state = self.search_or_explain(state)?;
+        let mut order = topological_order(&state, &package.name)?;
+        order.retain(|p| p.name != package.name);
+        Ok(order)
+    }
+
+    /// Picks releases for every requested package and their Reponere
+    /// dependencies, returned in installation order.
+    pub fn solve(&self, requests: &[Dependency]) -> Result<Vec<ResolvedPackage>, ResolveError> {
+        const ROOT: &str = "";
+
+        let names: Vec<&str> = requests.iter().map(|d| d.name.as_str()).collect();
+        let mut state = self.initial_state(&names);
+        for request in requests {
+            if self.registry.get_package(&request.name).is_none() {
+                return Err(ResolveError::NotInRegistry(request.name.clone()));
+            }
+            state.require(ROOT, request, "command line");
+        }
+
+        let state = self.search_or_explain(state)?;
+        let mut order = Vec::new();
+        let mut visiting = Vec::new();
+        for name in state.edges.get(ROOT).into_iter().flatten() {
+            visit_order(&state, name, &mut visiting, &mut order)?;
+        }
+        Ok(order)
+    }
+
+    fn initial_state(&self, replacing: &[&str]) -> SearchState {
+        let mut state = SearchState::default();
+        let Some(tracker) = self.installed else {
+            return state;
+        };
+
+        let mut installed: Vec<_> = tracker
+            .get_packages()
+            .values()
+            .filter(|p| !replacing.contains(&p.name.as_str()) && !self.removing.contains(&p.name))
+            .collect();
+        installed.sort_by(|a, b| a.name.cmp(&b.name));
+
+        for package in installed {
+            let required_by = format!("installed {}@{}", package.name, package.version);
+            for dependency in &package.dependencies {
+                if dependency.source == Some(DependencySource::Reponere) {
+                    state
+                        .constraints
+                        .entry(dependency.name.clone())
+                        .or_default()
+                        .push(Constraint {
+                            version_req: dependency.version_req.clone(),
+                            required_by: required_by.clone(),
+                        });
+                }
+            }
+        }
+        state
+    }
+
+    fn search_or_explain(&self, state: SearchState) -> Result<SearchState, ResolveError> {
+        let mut conflicts = Vec::new();
+        match self.search(state, &mut conflicts)? {
+            Some(state) => Ok(state),
+            None => {
+                let mut unique = Vec::new();
+                for conflict in conflicts {
+                    if !unique.contains(&conflict) {
+                        unique.push(conflict);
+                    }
+                }
+                Err(ResolveError::NoSolution(unique))
+            }
+        }
+    }
+
+    fn search(
+        &self,
+        mut state: SearchState,
+        conflicts: &mut Vec<String>,
+    ) -> Result<Option<SearchState>, ResolveError> {
+        let Some(name) = state.pending.first().cloned() else {
+            return Ok(Some(state));
+        };
+        state.pending.remove(0);
+
+        let Some(entry) = self.registry.get_package(&name) else {
+            conflicts.push(format!(
+                "{name} is not in the registry (required by {})",
+                describe_requirers(state.constraints_on(&name))
+            ));
+            return Ok(None);
+        };
+
+        let available = self.candidate_versions(&name, entry.releases.keys())?;
+        let mut candidates = Vec::new();
+        for version in &available {
+            if state.allows(&name, version)? {
+                candidates.push(version.clone());
+            }
+        }
+
+        if candidates.is_empty() {
+            conflicts.push(explain_no_candidates(
+                &name,
+                state.constraints_on(&name),
+                &available,
+            ));
+            return Ok(None);
+        }
+
+        // only the conflicts of the last candidate tried are kept, so a
+        // failure reports the final path instead of every dead end
+        let explored = conflicts.len();
+        'candidates: for version in candidates {
+            conflicts.truncate(explored);
+            let package = match self.load(&name, &version) {
+                Ok(package) => package,
+                Err(message) => {
+                    conflicts.push(format!("failed to parse {name}@{version}: {message}"));
+                    continue;
+                }
+            };
+
+            let mut next = state.clone();
+            next.selected.insert(name.clone(), version.clone());
+            let required_by = format!("{name}@{version}");
+
+            for dependency in self.reponere_dependencies(&package) {
+                next.require(&name, dependency, &required_by);
+
+                if let Some(selected) = next.selected.get(&dependency.name) {
+                    let constraints = next.constraints_on(&dependency.name);
+                    if !next.allows(&dependency.name, selected)? {
+                        conflicts.push(format!(
+                            "{required_by} cannot be used: {} {selected} was selected, \
+                             but it must satisfy {}",
+                            dependency.name,
+                            constraints
+                                .iter()
+                                .map(Constraint::describe)
+                                .collect::<Vec<_>>()
+                                .join(" and ")
+                        ));
+                        continue 'candidates;
+                    }
+                }
+            }
+
+            if let Some(solution) = self.search(next, conflicts)? {
+                return Ok(Some(solution));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Orders the releases of `name` from most to least preferred: the
+    /// installed version first, then newest to oldest.
+    fn candidate_versions<'v>(
+        &self,
+        name: &str,
+        versions: impl Iterator<Item = &'v String>,
+    ) -> Result<Vec<String>, ResolveError> {
+        let mut parsed = versions
+            .map(|v| Version::parse(v).map(|parsed| (parsed, v.clone())))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|source| ResolveError::InvalidVersion {
+                name: name.to_string(),
+                source,
+            })?;
+        parsed.sort_by(|(a, a_text), (b, b_text)| b.cmp(a).then_with(|| a_text.cmp(b_text)));
+        let mut versions: Vec<String> = parsed.into_iter().map(|(_, v)| v).collect();
+
+        let installed = self
+            .installed
+            .and_then(|tracker| tracker.get_package(name))
+            .map(|p| p.version.clone());
+        if let Some(position) = installed.and_then(|v| versions.iter().position(|x| *x == v)) {
+            let version = versions.remove(position);
+            versions.insert(0, version);
+        }
+
+        Ok(versions)
+    }
+
+    fn load(&self, name: &str, version: &str) -> Result<Package, String> {
+        let key = (name.to_string(), version.to_string());
+        if let Some(cached) = self.recipes.borrow().get(&key) {
+            return cached.clone();
+        }
+
+        let parsed = self
+            .registry
+            .resolve_release(name, Some(version))
+            .ok_or_else(|| format!("release {version} not found"))
+            .and_then(|release| {
+                PackageParser::new(release.build_file())
+                    .parse()
+                    .map_err(|e| e.to_string())
+            });
+
+        self.recipes.borrow_mut().insert(key, parsed.clone());
+        parsed
+    }
+
+    fn reponere_dependencies<'p>(&self, package: &'p Package) -> Vec<&'p Dependency> {
+        package
+            .dependencies
+            .runtime
+            .iter()
+            .chain(&package.dependencies.build)
+            .filter(|d| is_reponere_dependency(d, self.registry))
+            .collect()
+    }
+}
+
+fn describe_requirers(constraints: &[Constraint]) -> String {
+    constraints
+        .iter()
+        .map(|c| c.required_by.clone())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn explain_no_candidates(name: &str, constraints: &[Constraint], available: &[String]) -> String {
+    let mut lines = vec![format!("no release of {name} satisfies all requirements:")];
+    for constraint in constraints {
+        lines.push(format!("  - {}", constraint.describe()));
+    }
+    lines.push(format!("  available: {}", available.join(", ")));
+    lines.join("\n")
+}
+
+fn topological_order(
+    state: &SearchState,
+    root: &str,
+) -> Result<Vec<ResolvedPackage>, ResolveError> {
+    let mut order = Vec::new();
+    let mut visiting = Vec::new();
+    visit_order(state, root, &mut visiting, &mut order)?;
+    Ok(order)
+}
+
+fn visit_order(
+    state: &SearchState,
+    name: &str,
+    visiting: &mut Vec<String>,
+    order: &mut Vec<ResolvedPackage>,
+) -> Result<(), ResolveError> {
+    if let Some(start) = visiting.iter().position(|n| n == name) {
+        let mut cycle = visiting[start..].to_vec();
+        cycle.push(name.to_string());
+        return Err(ResolveError::Cycle(cycle));
+    }
+    if order.iter().any(|p| p.name == name) {
+        return Ok(());
+    }
+
+    visiting.push(name.to_string());
+    for dependency in state.edges.get(name).into_iter().flatten() {
+        visit_order(state, dependency, visiting, order)?;
+    }
+    visiting.pop();
+
+    order.push(ResolvedPackage {
+        name: name.to_string(),
+        version: state.selected[name].clone(),
+    });
+    Ok(())
+}
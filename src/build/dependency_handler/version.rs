@@ -1,70 +1,455 @@
-use version_compare::Version;
+use std::{cmp::Ordering, fmt};
 
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum VersionError {
+    #[error("empty version requirement")]
+    EmptyRequirement,
+
+    #[error("invalid version '{0}'")]
+    InvalidVersion(String),
+
+    #[error("invalid version requirement '{requirement}': {reason}")]
+    InvalidRequirement { requirement: String, reason: String },
+}
+
+/// A package version such as `1.4.2`, `2.0.0-rc.1`, or a distribution
+/// version such as `1:3.28.1-1`, `1:9.6p1-3` or `1.1.1w`.
+///
+/// Release components may mix digits and letters; runs of digits compare
+/// numerically and sort before runs of letters, so `1.1.1w` is newer than
+/// `1.1.1` and older than `1.1.2`.
+///
+/// A suffix after `-` that starts with a digit is a distribution revision and
+/// sorts after the plain version; any other suffix (or one after `~`) is a
+/// pre-release and sorts before it. Build metadata after `+` is ignored.
+#[derive(Debug, Clone)]
+pub struct Version {
+    epoch: u64,
+    release: Vec<String>,
+    pre: Option<String>,
+    revision: Option<String>,
+}
+
+impl Version {
+    pub fn parse(input: &str) -> Result<Self, VersionError> {
+        let invalid = || VersionError::InvalidVersion(input.to_string());
+
+        let trimmed = input.trim();
+        let trimmed = trimmed.split_once('+').map_or(trimmed, |(v, _)| v);
+        let trimmed = trimmed.strip_prefix('v').unwrap_or(trimmed);
+
+        let (epoch, rest) = match trimmed.split_once(':') {
+            Some((epoch, rest)) => (epoch.parse().map_err(|_| invalid())?, rest),
+            None => (0, trimmed),
+        };
+
+        let (core, pre, revision) = match rest.find(['-', '~']) {
+            Some(idx) => {
+                let suffix = &rest[idx + 1..];
+                if suffix.is_empty() {
+                    return Err(invalid());
+                }
+                let is_revision = rest.as_bytes()[idx] == b'-'
+                    && suffix.starts_with(|c: char| c.is_ascii_digit());
+                if is_revision {
+                    (&rest[..idx], None, Some(suffix.to_string()))
+                } else {
+                    (&rest[..idx], Some(suffix.to_string()), None)
+                }
+            }
+            None => (rest, None, None),
+        };
+
+        let release: Vec<String> = core.split('.').map(str::to_string).collect();
+        let valid_part =
+            |part: &String| !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric());
+        if !core.starts_with(|c: char| c.is_ascii_digit()) || !release.iter().all(valid_part) {
+            return Err(invalid());
+        }
+
+        Ok(Version {
+            epoch,
+            release,
+            pre,
+            revision,
+        })
+    }
+
+    pub fn is_prerelease(&self) -> bool {
+        self.pre.is_some()
+    }
+
+    fn component(&self, index: usize) -> &str {
+        self.release.get(index).map_or("0", String::as_str)
+    }
+
+    /// The leading number of every release component, e.g. `[1, 1, 1]` for
+    /// `1.1.1w`.
+    fn numbers(&self) -> Vec<u64> {
+        self.release
+            .iter()
+            .map(|part| {
+                let digits =
+                    part.len() - part.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+                part[..digits].parse().unwrap_or(0)
+            })
+            .collect()
+    }
+
+    fn cmp_release(&self, other: &Version) -> Ordering {
+        self.epoch.cmp(&other.epoch).then_with(|| {
+            let len = self.release.len().max(other.release.len());
+            (0..len)
+                .map(|i| compare_component(self.component(i), other.component(i)))
+                .find(|o| o.is_ne())
+                .unwrap_or(Ordering::Equal)
+        })
+    }
+
+    fn cmp_pre(&self, other: &Version) -> Ordering {
+        match (&self.pre, &other.pre) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) => compare_identifiers(a, b),
+        }
+    }
+
+    /// Compares against a requirement's version. A requirement that names no
+    /// distribution revision matches every revision of that version.
+    fn cmp_for_requirement(&self, required: &Version) -> Ordering {
+        let ordering = self
+            .cmp_release(required)
+            .then_with(|| self.cmp_pre(required));
+
+        match (&self.revision, &required.revision) {
+            (_, None) => ordering,
+            (None, Some(_)) => ordering.then(Ordering::Less),
+            (Some(a), Some(b)) => ordering.then_with(|| compare_identifiers(a, b)),
+        }
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cmp_release(other)
+            .then_with(|| self.cmp_pre(other))
+            .then_with(|| match (&self.revision, &other.revision) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Less,
+                (Some(_), None) => Ordering::Greater,
+                (Some(a), Some(b)) => compare_identifiers(a, b),
+            })
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Version {}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn compare_identifiers(a: &str, b: &str) -> Ordering {
+    compare_sequence(a.split(['.', '-', '_']), b.split(['.', '-', '_']))
+}
+
+/// Compares a release component such as `6p1` run by run: `6`, `p`, `1`.
+fn compare_component(a: &str, b: &str) -> Ordering {
+    compare_sequence(runs(a), runs(b))
+}
+
+fn runs(part: &str) -> impl Iterator<Item = &str> {
+    let mut rest = part;
+    std::iter::from_fn(move || {
+        let first = rest.chars().next()?;
+        let end = rest
+            .find(|c: char| c.is_ascii_digit() != first.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let (run, tail) = rest.split_at(end);
+        rest = tail;
+        Some(run)
+    })
+}
+
+/// Numeric identifiers compare numerically and sort before alphanumeric
+/// ones, which compare as text; a shorter sequence sorts first.
+fn compare_sequence<'a>(
+    mut left: impl Iterator<Item = &'a str>,
+    mut right: impl Iterator<Item = &'a str>,
+) -> Ordering {
+    loop {
+        match (left.next(), right.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => {
+                let ordering = match (x.parse::<u64>(), y.parse::<u64>()) {
+                    (Ok(x), Ok(y)) => x.cmp(&y),
+                    (Ok(_), Err(_)) => Ordering::Less,
+                    (Err(_), Ok(_)) => Ordering::Greater,
+                    (Err(_), Err(_)) => x.cmp(y),
+                };
+                if ordering.is_ne() {
+                    return ordering;
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum Operator {
     Gt,
     Ge,
     Lt,
     Le,
     Eq,
+    Ne,
 }
 
-pub struct VersionRequirement {
+#[derive(Debug, Clone, PartialEq)]
+struct Comparator {
     op: Operator,
-    version: String,
+    version: Version,
 }
 
-impl VersionRequirement {
-    fn new(op: Operator, version: String) -> Self {
-        VersionRequirement { op, version }
+impl Comparator {
+    fn matches(&self, version: &Version) -> bool {
+        let ordering = version.cmp_for_requirement(&self.version);
+        match self.op {
+            Operator::Gt => ordering.is_gt(),
+            Operator::Ge => ordering.is_ge(),
+            Operator::Lt => ordering.is_lt(),
+            Operator::Le => ordering.is_le(),
+            Operator::Eq => ordering.is_eq(),
+            Operator::Ne => ordering.is_ne(),
+        }
     }
+}
 
-    pub fn parse_requirement(req: &str) -> Self {
-        if let Some(rest) = req.strip_prefix(">=") {
-            return Self::new(Operator::Ge, rest.to_string());
-        }
-        if let Some(rest) = req.strip_prefix("<=") {
-            return Self::new(Operator::Le, rest.to_string());
-        }
-        if let Some(rest) = req.strip_prefix(">") {
-            return Self::new(Operator::Gt, rest.to_string());
+/// A comma-separated set of constraints that must all hold, e.g.
+/// `>=1.2, <2.0`, `^1.4`, `~1.4.2`, `1.*` or `!=1.3.0`. A bare version
+/// requires that exact version.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionRequirement {
+    comparators: Vec<Comparator>,
+}
+
+impl VersionRequirement {
+    pub fn parse_requirement(req: &str) -> Result<Self, VersionError> {
+        if req.trim().is_empty() {
+            return Err(VersionError::EmptyRequirement);
         }
-        if let Some(rest) = req.strip_prefix("<") {
-            return Self::new(Operator::Lt, rest.to_string());
+
+        let mut comparators = Vec::new();
+        for part in req.split(',') {
+            comparators.extend(parse_constraint(part.trim()).map_err(|reason| {
+                VersionError::InvalidRequirement {
+                    requirement: req.to_string(),
+                    reason,
+                }
+            })?);
         }
-        if let Some(rest) = req.strip_prefix("==") {
-            return Self::new(Operator::Eq, rest.to_string());
+
+        Ok(VersionRequirement { comparators })
+    }
+
+    pub fn matches(&self, dep_version: &str) -> Result<bool, VersionError> {
+        Version::parse(dep_version).map(|version| self.matches_version(&version))
+    }
+
+    /// Pre-releases only match when the requirement itself names a
+    /// pre-release of the same version, so `>=1.0` does not pick `2.0.0-rc.1`.
+    pub fn matches_version(&self, version: &Version) -> bool {
+        if version.is_prerelease()
+            && !self
+                .comparators
+                .iter()
+                .any(|c| c.version.is_prerelease() && c.version.cmp_release(version).is_eq())
+        {
+            return false;
         }
 
-        // Default to equality if no operator specified
-        Self::new(Operator::Eq, req.to_string())
+        self.comparators.iter().all(|c| c.matches(version))
     }
+}
 
-    pub fn matches(&self, dep_version: &str) -> bool {
-        let dep_version = match Version::from(dep_version) {
-            Some(v) => v,
-            None => return false,
-        };
+impl fmt::Display for VersionRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = self
+            .comparators
+            .iter()
+            .map(|c| {
+                let op = match c.op {
+                    Operator::Gt => ">",
+                    Operator::Ge => ">=",
+                    Operator::Lt => "<",
+                    Operator::Le => "<=",
+                    Operator::Eq => "==",
+                    Operator::Ne => "!=",
+                };
+                format!("{op}{}", format_version(&c.version))
+            })
+            .collect();
+        write!(f, "{}", parts.join(", "))
+    }
+}
 
-        let req_version = match Version::from(&self.version) {
-            Some(v) => v,
-            None => return false,
-        };
+fn format_version(version: &Version) -> String {
+    let mut out = String::new();
+    if version.epoch != 0 {
+        out.push_str(&format!("{}:", version.epoch));
+    }
+    out.push_str(&version.release.join("."));
+    if let Some(pre) = &version.pre {
+        out.push_str(&format!("-{pre}"));
+    }
+    if let Some(revision) = &version.revision {
+        out.push_str(&format!("-{revision}"));
+    }
+    out
+}
 
-        match self.op {
-            Operator::Gt => dep_version > req_version,
-            Operator::Ge => dep_version >= req_version,
-            Operator::Lt => dep_version < req_version,
-            Operator::Le => dep_version <= req_version,
-            Operator::Eq => dep_version == req_version,
+fn parse_constraint(constraint: &str) -> Result<Vec<Comparator>, String> {
+    if constraint.is_empty() {
+        return Err("empty constraint".to_string());
+    }
+
+    const OPERATORS: [(&str, Operator); 7] = [
+        (">=", Operator::Ge),
+        ("<=", Operator::Le),
+        ("==", Operator::Eq),
+        ("!=", Operator::Ne),
+        (">", Operator::Gt),
+        ("<", Operator::Lt),
+        ("=", Operator::Eq),
+    ];
+
+    for (prefix, op) in OPERATORS {
+        if let Some(rest) = constraint.strip_prefix(prefix) {
+            let version = parse_version(rest.trim())?;
+            return Ok(vec![Comparator { op, version }]);
         }
     }
+
+    if let Some(rest) = constraint.strip_prefix('^') {
+        return caret(rest.trim());
+    }
+    if let Some(rest) = constraint.strip_prefix('~') {
+        return tilde(rest.trim());
+    }
+    if constraint
+        .split('.')
+        .any(|part| matches!(part, "*" | "x" | "X"))
+    {
+        return wildcard(constraint);
+    }
+
+    let version = parse_version(constraint)?;
+    Ok(vec![Comparator {
+        op: Operator::Eq,
+        version,
+    }])
+}
+
+fn parse_version(input: &str) -> Result<Version, String> {
+    Version::parse(input).map_err(|e| e.to_string())
+}
+
+fn release_version(release: Vec<u64>) -> Version {
+    Version {
+        epoch: 0,
+        release: release.iter().map(u64::to_string).collect(),
+        pre: None,
+        revision: None,
+    }
+}
+
+fn range(lower: Version, upper: Vec<u64>) -> Vec<Comparator> {
+    vec![
+        Comparator {
+            op: Operator::Ge,
+            version: lower,
+        },
+        Comparator {
+            op: Operator::Lt,
+            version: release_version(upper),
+        },
+    ]
+}
+
+fn caret(input: &str) -> Result<Vec<Comparator>, String> {
+    let version = parse_version(input)?;
+    let parts = &version.numbers();
+
+    // The upper bound bumps the left-most non-zero component given.
+    let upper = match parts.iter().position(|&p| p != 0) {
+        Some(idx) => {
+            let mut upper = parts[..=idx].to_vec();
+            upper[idx] += 1;
+            upper
+        }
+        None => {
+            let mut upper = parts.to_vec();
+            *upper.last_mut().unwrap() += 1;
+            upper
+        }
+    };
+
+    Ok(range(version, upper))
+}
+
+fn tilde(input: &str) -> Result<Vec<Comparator>, String> {
+    let version = parse_version(input)?;
+
+    let keep = if version.release.len() > 1 { 2 } else { 1 };
+    let mut upper = version.numbers()[..keep].to_vec();
+    upper[keep - 1] += 1;
+
+    Ok(range(version, upper))
 }
 
-pub fn is_newer(a: &str, b: &str) -> bool {
-    match (Version::from(a), Version::from(b)) {
-        (Some(v_a), Some(v_b)) => v_a > v_b,
-        _ => false,
+fn wildcard(input: &str) -> Result<Vec<Comparator>, String> {
+    let parts: Vec<&str> = input.split('.').collect();
+    let fixed = parts
+        .iter()
+        .position(|part| matches!(*part, "*" | "x" | "X"))
+        .unwrap();
+
+    if parts[fixed + 1..]
+        .iter()
+        .any(|part| !matches!(*part, "*" | "x" | "X"))
+    {
+        return Err(format!("wildcard must be the last component in '{input}'"));
+    }
+    if fixed == 0 {
+        return Ok(Vec::new());
     }
+
+    let lower = parts[..fixed]
+        .iter()
+        .map(|part| part.parse::<u64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| format!("invalid version '{input}'"))?;
+
+    let mut upper = lower.clone();
+    upper[fixed - 1] += 1;
+
+    Ok(range(release_version(lower), upper))
+}
+
+pub fn is_newer(a: &str, b: &str) -> Result<bool, VersionError> {
+    Ok(Version::parse(a)? > Version::parse(b)?)
 }
 
 #[cfg(test)]
@@ -73,28 +458,78 @@ mod tests {
 
     #[test]
     fn test_parse_requirement() {
-        let req = VersionRequirement::parse_requirement(">1.2.3");
-        assert_eq!(matches!(req.op, Operator::Gt), true);
-        assert_eq!(req.version, "1.2.3");
+        let req = VersionRequirement::parse_requirement(">1.2.3").unwrap();
+        assert_eq!(req.comparators[0].op, Operator::Gt);
+        assert_eq!(req.comparators[0].version, Version::parse("1.2.3").unwrap());
+
+        let req = VersionRequirement::parse_requirement(">=2.0.0").unwrap();
+        assert_eq!(req.comparators[0].op, Operator::Ge);
 
-        let req = VersionRequirement::parse_requirement(">=2.0.0");
-        assert_eq!(matches!(req.op, Operator::Ge), true);
-        assert_eq!(req.version, "2.0.0");
+        let req = VersionRequirement::parse_requirement("<3.0.0").unwrap();
+        assert_eq!(req.comparators[0].op, Operator::Lt);
 
-        let req = VersionRequirement::parse_requirement("<3.0.0");
-        assert_eq!(matches!(req.op, Operator::Lt), true);
-        assert_eq!(req.version, "3.0.0");
+        let req = VersionRequirement::parse_requirement("<=4.5.6").unwrap();
+        assert_eq!(req.comparators[0].op, Operator::Le);
 
-        let req = VersionRequirement::parse_requirement("<=4.5.6");
-        assert_eq!(matches!(req.op, Operator::Le), true);
-        assert_eq!(req.version, "4.5.6");
+        let req = VersionRequirement::parse_requirement("==1.0.0").unwrap();
+        assert_eq!(req.comparators[0].op, Operator::Eq);
 
-        let req = VersionRequirement::parse_requirement("==1.0.0");
-        assert_eq!(matches!(req.op, Operator::Eq), true);
-        assert_eq!(req.version, "1.0.0");
+        let req = VersionRequirement::parse_requirement("7.8.9").unwrap(); // no operator
+        assert_eq!(req.comparators[0].op, Operator::Eq);
+        assert_eq!(req.comparators[0].version, Version::parse("7.8.9").unwrap());
+    }
+
+    #[test]
+    fn test_parse_compound_requirement() {
+        let req = VersionRequirement::parse_requirement(">=1.2, <2.0").unwrap();
+        assert_eq!(req.to_string(), ">=1.2, <2.0");
+
+        let req = VersionRequirement::parse_requirement("^0.4.1").unwrap();
+        assert_eq!(req.to_string(), ">=0.4.1, <0.5");
+
+        let req = VersionRequirement::parse_requirement("~1.4.2").unwrap();
+        assert_eq!(req.to_string(), ">=1.4.2, <1.5");
+
+        let req = VersionRequirement::parse_requirement("1.*").unwrap();
+        assert_eq!(req.to_string(), ">=1, <2");
+
+        let req = VersionRequirement::parse_requirement("*").unwrap();
+        assert!(req.comparators.is_empty());
+    }
 
-        let req = VersionRequirement::parse_requirement("7.8.9"); // no operator
-        assert_eq!(matches!(req.op, Operator::Eq), true);
-        assert_eq!(req.version, "7.8.9");
+    #[test]
+    fn test_distribution_versions() {
+        let matches = |req: &str, version: &str| {
+            VersionRequirement::parse_requirement(req)
+                .unwrap()
+                .matches(version)
+                .unwrap()
+        };
+
+        assert!(matches(">=1:9.6, <1:10", "1:9.6p1-3"));
+        assert!(!matches(">=1:9.7", "1:9.6p1-3"));
+        assert!(is_newer("1:9.6p1-3", "1:9.6-1").unwrap());
+        assert!(is_newer("1:9.10p1-1", "1:9.6p1-3").unwrap());
+
+        assert!(matches(">=1.1.1, <1.1.2", "1.1.1w"));
+        assert!(matches("^1.1", "1.1.1w"));
+        assert!(is_newer("1.1.1w", "1.1.1v").unwrap());
+        assert!(is_newer("1.1.1w", "1.1.1").unwrap());
+        assert!(is_newer("1.1.2", "1.1.1w").unwrap());
+
+        assert!(is_newer("2.0", "2.0~rc1").unwrap());
+        assert!(matches(">=2.0~rc1", "2.0~rc1"));
+        assert!(!matches(">=1.0", "2.0~rc1"));
+    }
+
+    #[test]
+    fn test_invalid_versions_are_errors() {
+        let req = VersionRequirement::parse_requirement(">=1.0").unwrap();
+        assert_eq!(
+            req.matches("latest"),
+            Err(VersionError::InvalidVersion("latest".to_string()))
+        );
+        assert!(is_newer("1.0", "1..2").is_err());
+        assert!(is_newer("1.0-", "1.0").is_err());
     }
 }
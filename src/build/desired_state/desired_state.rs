@@ -0,0 +1,51 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::build::dependency_handler::version::{VersionError, VersionRequirement};
+
+#[derive(Debug, Error)]
+pub enum DesiredStateError {
+    #[error("Failed to read desired state: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Failed to parse TOML: {0}")]
+    TomlError(#[from] toml::de::Error),
+
+    #[error("Failed to parse YAML: {0}")]
+    YamlError(#[from] serde_yml::Error),
+
+    #[error("Invalid version requirement for {name}: {source}")]
+    InvalidRequirement { name: String, source: VersionError },
+}
+
+/// The set of packages that should be installed, mapping each package name
+/// to a version requirement (`"*"` accepts any version).
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct DesiredState {
+    #[serde(default)]
+    pub packages: BTreeMap<String, String>,
+}
+
+impl DesiredState {
+    pub fn load(path: &Path) -> Result<Self, DesiredStateError> {
+        let data = fs::read_to_string(path)?;
+
+        let state: DesiredState = match path.extension().and_then(|e| e.to_str()) {
+            Some("yaml") | Some("yml") => serde_yml::from_str(&data)?,
+            _ => toml::from_str(&data)?,
+        };
+
+        for (name, requirement) in &state.packages {
+            VersionRequirement::parse_requirement(requirement).map_err(|source| {
+                DesiredStateError::InvalidRequirement {
+                    name: name.clone(),
+                    source,
+                }
+            })?;
+        }
+
+        Ok(state)
+    }
+}
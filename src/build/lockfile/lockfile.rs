@@ -0,0 +1,142 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::build::package::package::Source;
+
+pub const LOCKFILE_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum LockfileError {
+    #[error("Failed to access lockfile: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Failed to read lockfile: {0}")]
+    DeserializeError(#[from] toml::de::Error),
+
+    #[error("Failed to write lockfile: {0}")]
+    SerializeError(#[from] toml::ser::Error),
+
+    #[error("Unsupported lockfile version {0}")]
+    UnsupportedVersion(u32),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Lockfile {
+    pub version: u32,
+    #[serde(default, rename = "package")]
+    pub packages: Vec<LockedPackage>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: String,
+    pub recipe_sha256: String,
+    pub source: LockedSource,
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    #[serde(default)]
+    pub system_dependencies: Vec<LockedSystemDependency>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum LockedSource {
+    Git { repo: String, commit: String },
+    Archive { url: String, sha256: String },
+    Local { path: String, blake3: String },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LockedSystemDependency {
+    pub name: String,
+    pub version: Option<String>,
+}
+
+impl Default for Lockfile {
+    fn default() -> Self {
+        Lockfile {
+            version: LOCKFILE_VERSION,
+            packages: Vec::new(),
+        }
+    }
+}
+
+impl Lockfile {
+    pub fn load(path: &Path) -> Result<Self, LockfileError> {
+        let lockfile: Lockfile = toml::from_str(&fs::read_to_string(path)?)?;
+        if lockfile.version != LOCKFILE_VERSION {
+            return Err(LockfileError::UnsupportedVersion(lockfile.version));
+        }
+        Ok(lockfile)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), LockfileError> {
+        fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn get_package(&self, name: &str) -> Option<&LockedPackage> {
+        self.packages.iter().find(|p| p.name == name)
+    }
+}
+
+impl LockedSource {
+    /// Returns `source` pinned to exactly what was locked.
+    pub fn pin(&self, source: &Source) -> Source {
+        match (self, source.clone()) {
+            (
+                LockedSource::Git { commit, .. },
+                Source::Git {
+                    repo,
+                    verify_signature,
+                    signing_keys,
+                    submodules,
+                    lfs,
+                    ..
+                },
+            ) => Source::Git {
+                repo,
+                tag: None,
+                branch: None,
+                commit: Some(commit.clone()),
+                expected_commit: None,
+                verify_signature,
+                signing_keys,
+                submodules,
+                lfs,
+            },
+            (
+                LockedSource::Archive { sha256, .. },
+                Source::Archive {
+                    url,
+                    blake3,
+                    strip_components,
+                    ..
+                },
+            ) => Source::Archive {
+                url,
+                sha256: Some(sha256.clone()),
+                blake3,
+                strip_components,
+            },
+            (_, source) => source,
+        }
+    }
+
+    pub fn matches(&self, source: &Source) -> bool {
+        match (self, source) {
+            (LockedSource::Git { repo, .. }, Source::Git { repo: other, .. }) => repo == other,
+            (LockedSource::Archive { url, .. }, Source::Archive { url: other, .. }) => url == other,
+            (LockedSource::Local { path, .. }, Source::Local { path: other, .. }) => path == other,
+            _ => false,
+        }
+    }
+}
+
+pub fn recipe_sha256(path: &Path) -> Result<String, std::io::Error> {
+    Ok(hex::encode(Sha256::digest(fs::read(path)?)))
+}
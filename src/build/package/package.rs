@@ -1,27 +1,114 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Package {
     pub name: String,
     pub version: String,
     pub description: Option<String>,
+    /// SPDX license expression.
+    pub license: Option<String>,
+    pub homepage: Option<String>,
+    #[serde(default)]
+    pub maintainers: Vec<String>,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    /// Other names this package can stand in for, e.g. `vi` for a vi clone.
+    #[serde(default)]
+    pub provides: Vec<String>,
 
     pub source: Source,
+    #[serde(default)]
+    pub patches: Vec<Patch>,
     pub dependencies: Dependencies,
     pub build: Option<Build>,
     pub install_path: Option<String>,
 }
 
+impl Package {
+    pub fn metadata(&self) -> PackageMetadata {
+        PackageMetadata {
+            description: self.description.clone(),
+            license: self.license.clone(),
+            homepage: self.homepage.clone(),
+            maintainers: self.maintainers.clone(),
+            keywords: self.keywords.clone(),
+            categories: self.categories.clone(),
+            provides: self.provides.clone(),
+        }
+    }
+}
+
+/// The descriptive part of a recipe, kept in the registry index so it can
+/// be shown without parsing recipes again.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct PackageMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub maintainers: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub provides: Vec<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct InstalledPackage {
     pub name: String,
     pub version: String,
     pub install_path: String,
     pub dependencies: Vec<Dependency>,
+    #[serde(default)]
+    pub manifest: Manifest,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Manifest {
+    pub files: Vec<String>,
+    pub directories: Vec<String>,
+}
+
+impl Manifest {
+    pub fn difference(&self, other: &Manifest) -> Manifest {
+        Manifest {
+            files: self
+                .files
+                .iter()
+                .filter(|f| !other.files.contains(f))
+                .cloned()
+                .collect(),
+            directories: self
+                .directories
+                .iter()
+                .filter(|d| !other.directories.contains(d))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty() && self.directories.is_empty()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InstalledBuild {
+    pub version: String,
+    pub install_path: String,
+    pub dependencies: Vec<Dependency>,
+    pub archive: String,
 }
 
-// TODO
-// support other sources
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub enum Source {
     Git {
@@ -29,12 +116,258 @@ pub enum Source {
         tag: Option<String>,
         branch: Option<String>,
         commit: Option<String>,
+        expected_commit: Option<String>,
+        verify_signature: Option<bool>,
+        signing_keys: Option<Vec<String>>,
+        submodules: Option<Submodules>,
+        lfs: Option<bool>,
+    },
+    Archive {
+        url: String,
+        sha256: Option<String>,
+        blake3: Option<String>,
+        strip_components: Option<usize>,
+    },
+    Local {
+        path: String,
+        ignore: Option<Vec<String>>,
     },
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub enum Patch {
+    File { path: String },
+    Inline { diff: String },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[serde(try_from = "SubmodulesRepr", into = "SubmodulesRepr")]
+pub enum Submodules {
+    #[default]
+    Disabled,
+    Enabled,
+    Recursive,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum SubmodulesRepr {
+    Bool(bool),
+    Mode(String),
+}
+
+impl TryFrom<SubmodulesRepr> for Submodules {
+    type Error = String;
+
+    fn try_from(repr: SubmodulesRepr) -> Result<Self, Self::Error> {
+        match repr {
+            SubmodulesRepr::Bool(false) => Ok(Submodules::Disabled),
+            SubmodulesRepr::Bool(true) => Ok(Submodules::Enabled),
+            SubmodulesRepr::Mode(mode) if mode == "recursive" => Ok(Submodules::Recursive),
+            SubmodulesRepr::Mode(mode) => Err(format!(
+                "invalid submodules value {mode:?}, expected true, false or \"recursive\""
+            )),
+        }
+    }
+}
+
+impl From<Submodules> for SubmodulesRepr {
+    fn from(submodules: Submodules) -> Self {
+        match submodules {
+            Submodules::Disabled => SubmodulesRepr::Bool(false),
+            Submodules::Enabled => SubmodulesRepr::Bool(true),
+            Submodules::Recursive => SubmodulesRepr::Mode("recursive".to_string()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct Build {
+    /// Build system whose standard commands fill the phases the recipe
+    /// leaves out.
+    #[serde(default)]
+    pub helper: Option<BuildHelper>,
+    /// Extra arguments for the helper's main command, e.g. cmake `-D` flags.
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub prepare: Option<Vec<String>>,
+    #[serde(default)]
+    pub configure: Option<Vec<String>>,
+    #[serde(default)]
+    pub build: Option<Vec<String>>,
+    #[serde(default)]
+    pub check: Option<Vec<String>>,
+    #[serde(default)]
+    pub install: Option<Vec<String>>,
+    /// Raw shell steps run at the end of the build phase.
+    #[serde(default)]
     pub steps: Vec<String>,
+    /// Keeps network access for the build steps when they run sandboxed.
+    #[serde(default)]
+    pub network: bool,
+    /// Extra variables for the build steps.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+impl Build {
+    /// The commands of every phase in the order they run. A phase given in
+    /// the recipe replaces the helper's commands for it, so `check: []`
+    /// turns the helper's tests off.
+    pub fn phases(&self) -> Vec<(BuildPhase, Vec<String>)> {
+        BuildPhase::ALL
+            .into_iter()
+            .map(|phase| {
+                let explicit = match phase {
+                    BuildPhase::Prepare => &self.prepare,
+                    BuildPhase::Configure => &self.configure,
+                    BuildPhase::Build => &self.build,
+                    BuildPhase::Check => &self.check,
+                    BuildPhase::Install => &self.install,
+                };
+                let mut commands = match (explicit, self.helper) {
+                    (Some(commands), _) => commands.clone(),
+                    (None, Some(helper)) => helper.commands(phase, &self.args),
+                    (None, None) => Vec::new(),
+                };
+                if phase == BuildPhase::Build {
+                    commands.extend(self.steps.iter().cloned());
+                }
+                (phase, commands)
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildPhase {
+    Prepare,
+    Configure,
+    Build,
+    Check,
+    Install,
+}
+
+impl BuildPhase {
+    pub const ALL: [BuildPhase; 5] = [
+        BuildPhase::Prepare,
+        BuildPhase::Configure,
+        BuildPhase::Build,
+        BuildPhase::Check,
+        BuildPhase::Install,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BuildPhase::Prepare => "prepare",
+            BuildPhase::Configure => "configure",
+            BuildPhase::Build => "build",
+            BuildPhase::Check => "check",
+            BuildPhase::Install => "install",
+        }
+    }
+}
+
+/// Standard commands for common build systems. They refer to the
+/// variables every build gets, so `$PREFIX`, `$JOBS` and `$DESTDIR` come
+/// from the config and the staging dir.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum BuildHelper {
+    Cargo,
+    Cmake,
+    Meson,
+    Autotools,
+    Make,
+    PythonPip,
+}
+
+impl BuildHelper {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BuildHelper::Cargo => "cargo",
+            BuildHelper::Cmake => "cmake",
+            BuildHelper::Meson => "meson",
+            BuildHelper::Autotools => "autotools",
+            BuildHelper::Make => "make",
+            BuildHelper::PythonPip => "python-pip",
+        }
+    }
+
+    /// The commands for `phase`, with `args` added to the main command:
+    /// configure for cmake, meson and autotools, build for make, build and
+    /// install for cargo, and install for pip.
+    pub fn commands(&self, phase: BuildPhase, args: &[String]) -> Vec<String> {
+        let with_args = |command: &str| {
+            std::iter::once(command)
+                .chain(args.iter().map(String::as_str))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        match (self, phase) {
+            (BuildHelper::Cargo, BuildPhase::Build) => {
+                vec![with_args("cargo build --release --locked -j \"$JOBS\"")]
+            }
+            (BuildHelper::Cargo, BuildPhase::Check) => {
+                vec![with_args("cargo test --release --locked -j \"$JOBS\"")]
+            }
+            (BuildHelper::Cargo, BuildPhase::Install) => vec![with_args(
+                "cargo install --path . --locked --no-track -j \"$JOBS\" --root \"$DESTDIR$PREFIX\"",
+            )],
+
+            (BuildHelper::Cmake, BuildPhase::Configure) => vec![with_args(
+                "cmake -B build -DCMAKE_BUILD_TYPE=Release -DCMAKE_INSTALL_PREFIX=\"$PREFIX\"",
+            )],
+            (BuildHelper::Cmake, BuildPhase::Build) => {
+                vec!["cmake --build build -j \"$JOBS\"".to_string()]
+            }
+            (BuildHelper::Cmake, BuildPhase::Check) => {
+                vec!["ctest --test-dir build -j \"$JOBS\"".to_string()]
+            }
+            (BuildHelper::Cmake, BuildPhase::Install) => {
+                vec!["DESTDIR=\"$DESTDIR\" cmake --install build".to_string()]
+            }
+
+            (BuildHelper::Meson, BuildPhase::Configure) => vec![with_args(
+                "meson setup build --prefix=\"$PREFIX\" --buildtype=release",
+            )],
+            (BuildHelper::Meson, BuildPhase::Build) => {
+                vec!["meson compile -C build -j \"$JOBS\"".to_string()]
+            }
+            (BuildHelper::Meson, BuildPhase::Check) => {
+                vec!["meson test -C build".to_string()]
+            }
+            (BuildHelper::Meson, BuildPhase::Install) => {
+                vec!["meson install -C build --destdir \"$DESTDIR\"".to_string()]
+            }
+
+            (BuildHelper::Autotools, BuildPhase::Configure) => {
+                vec![with_args("./configure --prefix=\"$PREFIX\"")]
+            }
+            (BuildHelper::Autotools, BuildPhase::Build) => vec!["make -j\"$JOBS\"".to_string()],
+            (BuildHelper::Autotools, BuildPhase::Check) => vec!["make check".to_string()],
+            (BuildHelper::Autotools, BuildPhase::Install) => {
+                vec!["make DESTDIR=\"$DESTDIR\" install".to_string()]
+            }
+
+            (BuildHelper::Make, BuildPhase::Build) => {
+                vec![with_args("make -j\"$JOBS\" PREFIX=\"$PREFIX\"")]
+            }
+            (BuildHelper::Make, BuildPhase::Check) => vec!["make check".to_string()],
+            (BuildHelper::Make, BuildPhase::Install) => {
+                vec!["make PREFIX=\"$PREFIX\" DESTDIR=\"$DESTDIR\" install".to_string()]
+            }
+
+            (BuildHelper::PythonPip, BuildPhase::Install) => vec![with_args(
+                "python3 -m pip install --no-deps --no-cache-dir --no-warn-script-location \
+                 --prefix \"$PREFIX\" --root \"$DESTDIR\" .",
+            )],
+
+            _ => Vec::new(),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -43,8 +376,17 @@ pub struct Dependencies {
     pub build: Vec<Dependency>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Dependency {
     pub name: String,
     pub version_req: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<DependencySource>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencySource {
+    Reponere,
+    System,
 }
@@ -1,4 +1,4 @@
-use crate::build::package::package::Package;
+use crate::build::{dependency_handler::version::VersionRequirement, package::package::Package};
 use std::{fs::File, io};
 
 pub struct PackageParser {
@@ -15,7 +15,19 @@ impl PackageParser {
     pub fn parse(&self) -> Result<Package, Box<dyn std::error::Error>> {
         let file = File::open(&self.filename)?;
         let reader = io::BufReader::new(file);
-        let package = serde_yml::from_reader(reader)?;
+        let package: Package = serde_yml::from_reader(reader)?;
+
+        for dependency in package
+            .dependencies
+            .runtime
+            .iter()
+            .chain(&package.dependencies.build)
+        {
+            if let Some(req) = &dependency.version_req {
+                VersionRequirement::parse_requirement(req)?;
+            }
+        }
+
         Ok(package)
     }
 }
@@ -122,7 +122,7 @@ impl PackageManager {
                         continue;
                     }
 
-                    let Some(dep_part) = line.splitn(2, ':').nth(1) else {
+                    let Some(dep_part) = line.split_once(':').map(|(_, deps)| deps) else {
                         continue;
                     };
 
@@ -139,15 +139,15 @@ impl PackageManager {
                         continue;
                     }
 
-                    let Some(dep_part) = line.splitn(2, ':').nth(1) else {
+                    let Some(dep_part) = line.split_once(':').map(|(_, deps)| deps) else {
                         continue;
                     };
 
-                    let Some(dep) = dep_part.split_whitespace().next() else {
-                        continue;
-                    };
-
-                    deps.push(dep.to_string());
+                    for dep in dep_part.split(',') {
+                        if let Some(dep) = dep.split_whitespace().next() {
+                            deps.push(dep.to_string());
+                        }
+                    }
                 }
             }
             ManagerKind::Dnf => {
@@ -250,6 +250,7 @@ impl PackageManagerApi for PackageManager {
     }
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
 
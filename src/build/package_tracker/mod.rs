@@ -1 +1,2 @@
+#[allow(clippy::module_inception)]
 pub mod package_tracker;
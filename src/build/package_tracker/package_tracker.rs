@@ -1,4 +1,4 @@
-use crate::build::package::package::InstalledPackage;
+use crate::build::package::package::{InstalledBuild, InstalledPackage};
 
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, fs};
@@ -16,6 +16,8 @@ pub enum PackageTrackerError {
 #[derive(Default, Serialize, Deserialize, Debug)]
 pub struct PackageTracker {
     packages: HashMap<String, InstalledPackage>,
+    #[serde(default)]
+    history: HashMap<String, Vec<InstalledBuild>>,
 }
 
 impl PackageTracker {
@@ -49,6 +51,38 @@ impl PackageTracker {
         &self.packages
     }
 
+    pub fn owner_of(&self, path: &str) -> Option<&InstalledPackage> {
+        self.packages
+            .values()
+            .find(|pkg| pkg.manifest.files.iter().any(|f| f == path))
+    }
+
+    pub fn take_ownership(&mut self, package: &str, files: &[String]) {
+        for pkg in self.packages.values_mut().filter(|pkg| pkg.name != package) {
+            pkg.manifest.files.retain(|f| !files.contains(f));
+        }
+    }
+
+    pub fn history(&self, name: &str) -> &[InstalledBuild] {
+        self.history.get(name).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Appends `build` as the most recent build of `name`, replacing an older
+    /// build of the same version, and returns the builds dropped past `limit`.
+    pub fn record_build(
+        &mut self,
+        name: &str,
+        build: InstalledBuild,
+        limit: usize,
+    ) -> Vec<InstalledBuild> {
+        let builds = self.history.entry(name.to_string()).or_default();
+        builds.retain(|b| b.version != build.version);
+        builds.push(build);
+
+        let excess = builds.len().saturating_sub(limit.max(1));
+        builds.drain(..excess).collect()
+    }
+
     pub fn dependency_usage_count(&self, dependency_name: &str) -> usize {
         self.packages
             .values()
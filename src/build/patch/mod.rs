@@ -0,0 +1 @@
+pub mod patch_handler;
@@ -0,0 +1,100 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use git2::{ApplyLocation, Diff, Repository};
+use thiserror::Error;
+
+use crate::{build::package::package::Patch, handlers::events::InstallEvent};
+
+#[derive(Debug, Error)]
+pub enum PatchHandlerError {
+    #[error("Failed to read patch {name}: {source}")]
+    FailedReadingPatch {
+        name: String,
+        source: std::io::Error,
+    },
+
+    #[error("Failed to apply patch {name}: {message}")]
+    FailedApplyingPatch { name: String, message: String },
+
+    #[error("Failed to prepare source tree for patching: {0}")]
+    RepositoryError(#[from] git2::Error),
+
+    #[error("Failed to clean up after patching: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+pub struct PatchHandler {
+    pub patches: Vec<Patch>,
+    base_dir: PathBuf,
+}
+
+impl PatchHandler {
+    pub fn new<P: Into<PathBuf>>(patches: Vec<Patch>, base_dir: P) -> Self {
+        PatchHandler {
+            patches,
+            base_dir: base_dir.into(),
+        }
+    }
+
+    pub fn apply_patches<F: FnMut(InstallEvent)>(
+        &self,
+        path: &Path,
+        progress: &mut F,
+    ) -> Result<(), PatchHandlerError> {
+        if self.patches.is_empty() {
+            return Ok(());
+        }
+
+        let (repo, created) = match Repository::open(path) {
+            Ok(repo) => (repo, false),
+            Err(_) => (Repository::init(path)?, true),
+        };
+
+        let result = self.patches.iter().enumerate().try_for_each(|(i, patch)| {
+            let name = patch_name(patch, i);
+            progress(InstallEvent::ApplyingPatch { name: name.clone() });
+            self.apply_patch(&repo, patch, &name)
+        });
+
+        if created {
+            fs::remove_dir_all(path.join(".git"))?;
+        }
+        result
+    }
+
+    fn apply_patch(
+        &self,
+        repo: &Repository,
+        patch: &Patch,
+        name: &str,
+    ) -> Result<(), PatchHandlerError> {
+        let diff = match patch {
+            Patch::File { path } => fs::read(self.base_dir.join(path)).map_err(|source| {
+                PatchHandlerError::FailedReadingPatch {
+                    name: name.to_string(),
+                    source,
+                }
+            })?,
+            Patch::Inline { diff } => diff.clone().into_bytes(),
+        };
+
+        let failed = |e: git2::Error| PatchHandlerError::FailedApplyingPatch {
+            name: name.to_string(),
+            message: e.message().to_string(),
+        };
+
+        let diff = Diff::from_buffer(&diff).map_err(failed)?;
+        repo.apply(&diff, ApplyLocation::WorkDir, None)
+            .map_err(failed)
+    }
+}
+
+fn patch_name(patch: &Patch, index: usize) -> String {
+    match patch {
+        Patch::File { path } => path.clone(),
+        Patch::Inline { .. } => format!("inline patch #{}", index + 1),
+    }
+}
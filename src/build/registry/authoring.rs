@@ -0,0 +1,297 @@
+use std::path::{Component, Path};
+
+use crate::build::{
+    build_step_handler::environment::PROVIDED,
+    package::package::{BuildHelper, Package, Source},
+    source::source_handler::GitSource,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BuildSystem {
+    Cargo,
+    Meson,
+    CMake,
+    Autotools,
+    Make,
+}
+
+impl BuildSystem {
+    /// Detects the build system from the file names at the root of a
+    /// source tree.
+    pub fn detect<S: AsRef<str>>(files: &[S]) -> Option<BuildSystem> {
+        let has = |name: &str| files.iter().any(|f| f.as_ref() == name);
+
+        if has("Cargo.toml") {
+            Some(BuildSystem::Cargo)
+        } else if has("meson.build") {
+            Some(BuildSystem::Meson)
+        } else if has("CMakeLists.txt") {
+            Some(BuildSystem::CMake)
+        } else if has("configure") || has("configure.ac") {
+            Some(BuildSystem::Autotools)
+        } else if has("Makefile") || has("makefile") || has("GNUmakefile") {
+            Some(BuildSystem::Make)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BuildSystem::Cargo => "cargo",
+            BuildSystem::Meson => "meson",
+            BuildSystem::CMake => "cmake",
+            BuildSystem::Autotools => "autotools",
+            BuildSystem::Make => "make",
+        }
+    }
+
+    pub fn build_dependencies(&self) -> &'static [&'static str] {
+        match self {
+            BuildSystem::Cargo => &["cargo"],
+            BuildSystem::Meson => &["meson", "ninja"],
+            BuildSystem::CMake => &["cmake"],
+            BuildSystem::Autotools => &["make"],
+            BuildSystem::Make => &["make"],
+        }
+    }
+
+    pub fn helper(&self) -> BuildHelper {
+        match self {
+            BuildSystem::Cargo => BuildHelper::Cargo,
+            BuildSystem::Meson => BuildHelper::Meson,
+            BuildSystem::CMake => BuildHelper::Cmake,
+            BuildSystem::Autotools => BuildHelper::Autotools,
+            BuildSystem::Make => BuildHelper::Make,
+        }
+    }
+}
+
+/// Picks the tag naming `version`, trying the common conventions in order.
+pub fn find_version_tag<S: AsRef<str>>(tags: &[S], name: &str, version: &str) -> Option<String> {
+    let candidates = [
+        format!("v{version}"),
+        version.to_string(),
+        format!("{name}-{version}"),
+        format!("{name}-v{version}"),
+        format!("release-{version}"),
+    ];
+
+    candidates
+        .into_iter()
+        .find(|candidate| tags.iter().any(|t| t.as_ref() == candidate))
+}
+
+pub struct RecipeTemplate<'a> {
+    pub name: &'a str,
+    pub version: &'a str,
+    pub repo: &'a str,
+    pub tag: Option<&'a str>,
+    pub build_system: Option<BuildSystem>,
+}
+
+impl RecipeTemplate<'_> {
+    /// Renders the recipe in the same layout as the hand-written ones.
+    pub fn render(&self) -> String {
+        let mut out = format!(
+            "name: {}\nversion: \"{}\"\ndescription: \"\"\n\nsource:\n  !Git\n  repo: \"{}\"\n",
+            self.name, self.version, self.repo
+        );
+        if let Some(tag) = self.tag {
+            out.push_str(&format!("  tag: \"{tag}\"\n"));
+        }
+
+        out.push_str("\ndependencies:\n  runtime:\n  build:\n");
+        for dependency in self
+            .build_system
+            .map(|b| b.build_dependencies())
+            .unwrap_or_default()
+        {
+            out.push_str(&format!("    - name: {dependency}\n"));
+        }
+
+        out.push_str("\nbuild:\n");
+        match self.build_system {
+            Some(build_system) => {
+                out.push_str(&format!("  helper: {}\n", build_system.helper().name()));
+            }
+            None => out.push_str(
+                "  steps:\n    - \"echo 'no build system detected, fill in the build steps' && exit 1\"\n",
+            ),
+        }
+        // cargo fetches crates during the build
+        if self.build_system == Some(BuildSystem::Cargo) {
+            out.push_str("  network: true\n");
+        }
+
+        out
+    }
+}
+
+/// Checks a parsed recipe for problems that parsing alone does not catch.
+/// `name` and `version` are taken from the recipe's place in the registry.
+pub fn lint_package(package: &Package, name: &str, version: &str) -> Vec<String> {
+    let mut issues = Vec::new();
+
+    if package.name.trim() != name {
+        issues.push(format!(
+            "name {:?} does not match its directory {name:?}",
+            package.name.trim()
+        ));
+    }
+    if package.version != version {
+        issues.push(format!(
+            "version {:?} does not match its directory {version:?}",
+            package.version
+        ));
+    }
+
+    match &package.source {
+        Source::Git { .. } => {
+            if let Err(e) = GitSource::from_source(&package.source) {
+                issues.push(format!("source: {e}"));
+            }
+        }
+        Source::Archive {
+            url,
+            sha256,
+            blake3,
+            ..
+        } => {
+            if url.is_empty() {
+                issues.push("source: archive url is empty".to_string());
+            }
+            if sha256.is_none() && blake3.is_none() {
+                issues.push("source: archive has no sha256 or blake3 checksum".to_string());
+            }
+        }
+        Source::Local { .. } => {}
+    }
+
+    if let Some(license) = &package.license
+        && !is_spdx_expression(license)
+    {
+        issues.push(format!("license {license:?} is not an SPDX expression"));
+    }
+
+    // install_path is legacy and optional, but must stay inside the prefix
+    if let Some(path) = &package.install_path
+        && Path::new(path)
+            .components()
+            .any(|c| c == Component::ParentDir)
+    {
+        issues.push(format!("install_path {path:?} must not contain .."));
+    }
+
+    for name in package.build.iter().flat_map(|b| b.env.keys()) {
+        if PROVIDED.contains(&name.as_str()) {
+            issues.push(format!("env sets {name}, which Reponere provides"));
+        }
+    }
+
+    let steps = package
+        .build
+        .iter()
+        .flat_map(|b| b.phases())
+        .flat_map(|(_, steps)| steps);
+    for step in steps {
+        if installs_files(&step) && !step.contains("DESTDIR") {
+            issues.push(format!("build step {step:?} installs outside $DESTDIR"));
+        }
+    }
+
+    issues
+}
+
+/// Checks the shape of an SPDX license expression such as
+/// `MIT OR Apache-2.0` or `GPL-2.0-or-later WITH Classpath-exception-2.0`.
+/// Identifiers are not checked against the SPDX license list.
+pub fn is_spdx_expression(expression: &str) -> bool {
+    let spaced = expression.replace('(', " ( ").replace(')', " ) ");
+    let tokens: Vec<&str> = spaced.split_whitespace().collect();
+
+    let mut position = 0;
+    parse_spdx_compound(&tokens, &mut position) && position == tokens.len()
+}
+
+fn parse_spdx_compound(tokens: &[&str], position: &mut usize) -> bool {
+    loop {
+        match tokens.get(*position) {
+            Some(&"(") => {
+                *position += 1;
+                if !parse_spdx_compound(tokens, position) || tokens.get(*position) != Some(&")") {
+                    return false;
+                }
+                *position += 1;
+            }
+            Some(id) if is_spdx_identifier(id) => {
+                *position += 1;
+                if tokens.get(*position) == Some(&"WITH") {
+                    *position += 1;
+                    if !tokens
+                        .get(*position)
+                        .is_some_and(|id| is_spdx_identifier(id))
+                    {
+                        return false;
+                    }
+                    *position += 1;
+                }
+            }
+            _ => return false,
+        }
+
+        match tokens.get(*position) {
+            Some(&"AND") | Some(&"OR") => *position += 1,
+            _ => return true,
+        }
+    }
+}
+
+fn is_spdx_identifier(token: &str) -> bool {
+    !["AND", "OR", "WITH", "(", ")"].contains(&token)
+        && token
+            .trim_end_matches('+')
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+        && !token.trim_end_matches('+').is_empty()
+}
+
+fn installs_files(step: &str) -> bool {
+    step.trim_start().starts_with("install ")
+        || step.contains("make install")
+        || step.contains("cargo install")
+        || step.contains("pip install")
+        || (step.contains(" install")
+            && (step.contains("cmake") || step.contains("meson") || step.contains("make")))
+}
+
+/// Fields that pin the exact old release and cannot carry over to a new one.
+const PINNED_FIELDS: &[&str] = &["commit", "expected_commit", "sha256", "blake3"];
+
+/// Rewrites the text of a recipe for `to`, keeping its layout. The version,
+/// and the tag and archive url where they contain the old version, are
+/// updated; pins for the old release are dropped and returned.
+pub fn bump_recipe(recipe: &str, from: &str, to: &str) -> (String, Vec<String>) {
+    let mut dropped = Vec::new();
+    let mut out = String::new();
+
+    for line in recipe.lines() {
+        let trimmed = line.trim_start();
+        let key = trimmed.split(':').next().unwrap_or_default().trim();
+
+        if PINNED_FIELDS.contains(&key) && trimmed.contains(':') {
+            dropped.push(key.to_string());
+            continue;
+        }
+
+        let line = match key {
+            "version" if !line.starts_with(' ') => format!("version: \"{to}\""),
+            "tag" | "url" => line.replace(from, to),
+            _ => line.to_string(),
+        };
+        out.push_str(&line);
+        out.push('\n');
+    }
+
+    (out, dropped)
+}
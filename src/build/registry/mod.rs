@@ -1 +1,4 @@
+pub mod authoring;
 pub mod registry_handler;
+pub mod remote;
+pub mod signature;
@@ -1,10 +1,19 @@
-use std::{collections::HashMap, fs, path::Path};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
 
-use crate::build::dependency_handler::version::is_newer;
+use crate::build::{
+    dependency_handler::version::{Version, is_newer},
+    package::{package::PackageMetadata, parse::PackageParser},
+};
 
 use serde::{Deserialize, Serialize};
 use walkdir::WalkDir;
 
+pub const LOCAL_REGISTRY: &str = "local";
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Registry {
     packages: HashMap<String, PackageEntry>,
@@ -14,17 +23,45 @@ pub struct Registry {
 pub struct PackageEntry {
     pub releases: HashMap<String, Release>,
     pub latest: String,
+    #[serde(default = "default_registry")]
+    pub registry: String,
+    /// Lower priority registries that also provide this package.
+    #[serde(default)]
+    pub shadowed: Vec<String>,
+    /// Taken from the recipe of the latest release.
+    #[serde(default)]
+    pub metadata: PackageMetadata,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Release {
     build_file: String,
+    #[serde(default = "default_registry")]
+    registry: String,
+}
+
+/// A directory of `name/version/package_build.yaml` recipes that takes part
+/// in the merged registry. Higher priorities win when several registries
+/// provide the same package.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegistrySource {
+    pub name: String,
+    pub path: PathBuf,
+    pub priority: i32,
+}
+
+fn default_registry() -> String {
+    LOCAL_REGISTRY.to_string()
 }
 
 impl Release {
     pub fn build_file(&self) -> &str {
         &self.build_file
     }
+
+    pub fn registry(&self) -> &str {
+        &self.registry
+    }
 }
 
 impl Registry {
@@ -47,12 +84,68 @@ impl Registry {
     }
 
     fn sync_from_directory(dir: &Path) -> Result<Self, std::io::Error> {
+        Registry::sync_from_sources(
+            &[RegistrySource {
+                name: LOCAL_REGISTRY.to_string(),
+                path: dir.to_path_buf(),
+                priority: 0,
+            }],
+            &BTreeMap::new(),
+        )
+    }
+
+    /// Merges `sources` into one registry. Each package comes from the
+    /// registry it is pinned to in `pins` if that registry provides it, and
+    /// otherwise from the highest priority registry that does. Ties go to
+    /// the source listed first.
+    fn sync_from_sources(
+        sources: &[RegistrySource],
+        pins: &BTreeMap<String, String>,
+    ) -> Result<Self, std::io::Error> {
+        let mut ordered: Vec<&RegistrySource> = sources.iter().collect();
+        ordered.sort_by_key(|source| std::cmp::Reverse(source.priority));
+
+        let mut candidates: HashMap<String, Vec<PackageEntry>> = HashMap::new();
+        for source in &ordered {
+            // registries mirrored below another one are only read on their own
+            let nested: Vec<&Path> = sources
+                .iter()
+                .filter(|other| other.path != source.path && other.path.starts_with(&source.path))
+                .map(|other| other.path.as_path())
+                .collect();
+
+            let mut entries = Registry::scan_source(source, &nested);
+            let mut names: Vec<String> = entries.keys().cloned().collect();
+            names.sort();
+            for name in names {
+                let entry = entries.remove(&name).unwrap();
+                candidates.entry(name).or_default().push(entry);
+            }
+        }
+
         let mut registry = Registry::new();
+        for (name, mut entries) in candidates {
+            let chosen = pins
+                .get(&name)
+                .and_then(|pinned| entries.iter().position(|e| e.registry == *pinned))
+                .unwrap_or(0);
+
+            let mut entry = entries.remove(chosen);
+            entry.shadowed = entries.into_iter().map(|e| e.registry).collect();
+            registry.packages.insert(name, entry);
+        }
+        Ok(registry)
+    }
 
-        for entry in WalkDir::new(dir)
-            .min_depth(2)
+    fn scan_source(source: &RegistrySource, excluded: &[&Path]) -> HashMap<String, PackageEntry> {
+        let mut packages: HashMap<String, PackageEntry> = HashMap::new();
+
+        // min_depth would bypass filter_entry, so the depth is checked here
+        for entry in WalkDir::new(&source.path)
             .into_iter()
+            .filter_entry(|e| !excluded.contains(&e.path()))
             .filter_map(|e| e.ok())
+            .filter(|e| e.depth() >= 2)
         {
             let path = entry.path();
 
@@ -68,22 +161,42 @@ impl Registry {
 
             let release = Release {
                 build_file: path.to_str().unwrap().to_string(),
+                registry: source.name.clone(),
             };
 
-            let entry = registry
-                .packages
+            let entry = packages
                 .entry(package_name.to_string())
                 .or_insert_with(|| PackageEntry {
                     releases: HashMap::new(),
                     latest: version.to_string(),
+                    registry: source.name.clone(),
+                    shadowed: Vec::new(),
+                    metadata: PackageMetadata::default(),
                 });
             entry.releases.insert(version.to_string(), release);
 
-            if is_newer(version, &entry.latest) {
+            // a release whose version cannot be parsed is listed but never
+            // becomes the latest; `registry lint` reports it
+            let newer = match is_newer(version, &entry.latest) {
+                Ok(newer) => newer,
+                Err(_) => Version::parse(version).is_ok(),
+            };
+            if newer {
                 entry.latest = version.to_string();
             }
         }
-        Ok(registry)
+
+        // recipes that fail to parse still get listed, without metadata
+        for entry in packages.values_mut() {
+            if let Some(package) = entry
+                .releases
+                .get(&entry.latest)
+                .and_then(|release| PackageParser::new(release.build_file()).parse().ok())
+            {
+                entry.metadata = package.metadata();
+            }
+        }
+        packages
     }
 
     pub fn load_or_sync(index: &Path, registry_dir: &Path) -> Self {
@@ -97,12 +210,33 @@ impl Registry {
         }
     }
 
+    pub fn load_or_sync_sources(
+        index: &Path,
+        sources: &[RegistrySource],
+        pins: &BTreeMap<String, String>,
+    ) -> Self {
+        match Registry::load_from_file(index) {
+            Ok(registry) => registry,
+            Err(_) => Registry::resync_sources_and_save(index, sources, pins),
+        }
+    }
+
     pub fn resync_from_directory_and_save(index: &Path, registry_dir: &Path) -> Self {
         let registry = Registry::sync_from_directory(registry_dir).unwrap();
         registry.save_to_file(index).unwrap();
         registry
     }
 
+    pub fn resync_sources_and_save(
+        index: &Path,
+        sources: &[RegistrySource],
+        pins: &BTreeMap<String, String>,
+    ) -> Self {
+        let registry = Registry::sync_from_sources(sources, pins).unwrap();
+        registry.save_to_file(index).unwrap();
+        registry
+    }
+
     pub fn resolve_release(&self, name: &str, version: Option<&str>) -> Option<&Release> {
         let package = self.packages.get(name)?;
 
@@ -160,6 +294,9 @@ mod tests {
             PackageEntry {
                 latest: "1.0.0".to_string(),
                 releases: HashMap::new(),
+                registry: LOCAL_REGISTRY.to_string(),
+                shadowed: Vec::new(),
+                metadata: PackageMetadata::default(),
             },
         );
 
@@ -0,0 +1,389 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::Read,
+    path::{Component, Path, PathBuf},
+};
+
+use git2::{ObjectType, Repository, ResetType, TreeWalkMode, TreeWalkResult};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use walkdir::WalkDir;
+
+use crate::build::registry::{
+    registry_handler::RegistrySource,
+    signature::{self, SignatureError},
+};
+
+pub const REMOTE_INDEX_FILE: &str = "index.json";
+const BUILD_FILE: &str = "package_build.yaml";
+
+#[derive(Debug, Error)]
+pub enum RemoteError {
+    #[error("io error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("git error: {0}")]
+    GitError(#[from] git2::Error),
+
+    #[error("failed to download {url}: {reason}")]
+    DownloadError { url: String, reason: String },
+
+    #[error("invalid remote index: {0}")]
+    InvalidIndex(String),
+
+    #[error("invalid path in remote index: {0}")]
+    InvalidPath(String),
+
+    #[error("{0}")]
+    SignatureError(#[from] SignatureError),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum RemoteSource {
+    Git {
+        url: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        branch: Option<String>,
+    },
+    Http {
+        url: String,
+    },
+    /// A directory that is used as-is, e.g. a personal overlay.
+    Local {
+        path: PathBuf,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteRegistry {
+    pub name: String,
+    #[serde(default)]
+    pub priority: i32,
+    /// Accept git and HTTP content whose index is not signed by a trusted
+    /// key. Local registries are never verified.
+    #[serde(default)]
+    pub allow_unsigned: bool,
+    #[serde(flatten)]
+    pub source: RemoteSource,
+}
+
+/// The index at the root of a remote registry, listing every release and
+/// any files besides `package_build.yaml` that belong to it. HTTP registries
+/// need it to know what to download; registries of either kind sign it, and it carries the checksum of every release file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RemoteIndex {
+    #[serde(default)]
+    pub packages: BTreeMap<String, BTreeMap<String, RemoteRelease>>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RemoteRelease {
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Release file, including `package_build.yaml`, to its sha256.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub sha256: BTreeMap<String, String>,
+}
+
+impl RemoteIndex {
+    /// Builds the index for a registry dir laid out as
+    /// `name/version/package_build.yaml`, skipping hidden files.
+    pub fn from_directory(dir: &Path) -> Result<Self, RemoteError> {
+        let mut index = RemoteIndex::default();
+
+        for entry in WalkDir::new(dir)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|e| e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'))
+        {
+            let entry = entry.map_err(std::io::Error::from)?;
+            if !entry.file_type().is_file() || entry.depth() < 3 {
+                continue;
+            }
+
+            let relative = entry.path().strip_prefix(dir).unwrap();
+            let mut components = relative.iter().map(|c| c.to_string_lossy().to_string());
+            let name = components.next().unwrap();
+            let version = components.next().unwrap();
+            let file = components.collect::<Vec<_>>().join("/");
+
+            let release = index
+                .packages
+                .entry(name)
+                .or_default()
+                .entry(version)
+                .or_default();
+            release.sha256.insert(
+                file.clone(),
+                hex::encode(Sha256::digest(fs::read(entry.path())?)),
+            );
+            if file != BUILD_FILE {
+                release.files.push(file);
+            }
+        }
+
+        index
+            .packages
+            .values_mut()
+            .for_each(|releases| releases.retain(|_, r| r.sha256.contains_key(BUILD_FILE)));
+        index.packages.retain(|_, releases| !releases.is_empty());
+        Ok(index)
+    }
+
+    /// Checks release files, keyed by their path relative to the registry
+    /// root, against the checksums in the index. Every file inside a release
+    /// dir must be listed and every listed file must be present.
+    pub fn verify_contents(
+        &self,
+        contents: &BTreeMap<String, Vec<u8>>,
+    ) -> Result<(), SignatureError> {
+        let mut expected = BTreeMap::new();
+        for (name, releases) in &self.packages {
+            for (version, release) in releases {
+                for (file, sha256) in &release.sha256 {
+                    expected.insert(format!("{name}/{version}/{file}"), sha256);
+                }
+            }
+        }
+
+        for (path, data) in contents {
+            if path.split('/').count() < 3 {
+                continue;
+            }
+            match expected.remove(path) {
+                Some(sha256) if *sha256 == hex::encode(Sha256::digest(data)) => {}
+                _ => return Err(SignatureError::Tampered { path: path.clone() }),
+            }
+        }
+
+        match expected.into_keys().next() {
+            Some(path) => Err(SignatureError::Tampered { path }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl RemoteRegistry {
+    pub fn url(&self) -> String {
+        match &self.source {
+            RemoteSource::Git { url, .. } | RemoteSource::Http { url } => url.clone(),
+            RemoteSource::Local { path } => path.display().to_string(),
+        }
+    }
+
+    /// The directory this remote is mirrored into below the registry dir,
+    /// or the directory itself for local registries.
+    pub fn local_path(&self, registry_dir: &Path) -> Result<PathBuf, RemoteError> {
+        match &self.source {
+            RemoteSource::Local { path } => Ok(path.clone()),
+            _ => Ok(registry_dir.join(checked_path(&self.name)?)),
+        }
+    }
+
+    pub fn registry_source(&self, registry_dir: &Path) -> Result<RegistrySource, RemoteError> {
+        Ok(RegistrySource {
+            name: self.name.clone(),
+            path: self.local_path(registry_dir)?,
+            priority: self.priority,
+        })
+    }
+
+    /// Mirrors the remote into its directory below `registry_dir`, replacing
+    /// whatever an earlier sync left there. Unless `allow_unsigned` is set,
+    /// the remote is verified against `trusted_keys` first and left
+    /// untouched if that fails.
+    pub fn sync(
+        &self,
+        registry_dir: &Path,
+        trusted_keys: &[String],
+    ) -> Result<PathBuf, RemoteError> {
+        let dest = self.local_path(registry_dir)?;
+        let trusted_keys = (!self.allow_unsigned).then_some(trusted_keys);
+
+        match &self.source {
+            RemoteSource::Git { url, branch } => {
+                sync_git(url, branch.as_deref(), &dest, trusted_keys)?
+            }
+            RemoteSource::Http { url } => sync_http(url, &dest, trusted_keys)?,
+            RemoteSource::Local { .. } => {}
+        }
+        Ok(dest)
+    }
+}
+
+/// Checks the signature over the raw index and returns the parsed index.
+fn verify_index(
+    index: &[u8],
+    signature: Option<&[u8]>,
+    trusted_keys: &[String],
+) -> Result<RemoteIndex, RemoteError> {
+    let signature =
+        signature.ok_or_else(|| SignatureError::Unsigned(REMOTE_INDEX_FILE.to_string()))?;
+    signature::verify(index, signature, trusted_keys)?;
+
+    serde_json::from_slice(index).map_err(|e| RemoteError::InvalidIndex(e.to_string()))
+}
+
+fn sync_git(
+    url: &str,
+    branch: Option<&str>,
+    dest: &Path,
+    trusted_keys: Option<&[String]>,
+) -> Result<(), RemoteError> {
+    let repo = match Repository::open(dest) {
+        Ok(repo) => repo,
+        Err(_) => {
+            if dest.exists() {
+                fs::remove_dir_all(dest)?;
+            }
+            fs::create_dir_all(dest)?;
+            Repository::init(dest)?
+        }
+    };
+
+    if repo.find_remote("origin").is_ok() {
+        repo.remote_set_url("origin", url)?;
+    } else {
+        repo.remote("origin", url)?;
+    }
+
+    let (refspec, tracking) = match branch {
+        Some(branch) => (
+            format!("+refs/heads/{branch}:refs/remotes/origin/{branch}"),
+            format!("refs/remotes/origin/{branch}"),
+        ),
+        None => (
+            "+HEAD:refs/remotes/origin/HEAD".to_string(),
+            "refs/remotes/origin/HEAD".to_string(),
+        ),
+    };
+    repo.find_remote("origin")?.fetch(&[refspec], None, None)?;
+
+    let target = repo.revparse_single(&tracking)?;
+    if let Some(trusted_keys) = trusted_keys {
+        verify_git_tree(&repo, &target, trusted_keys)?;
+    }
+    repo.reset(&target, ResetType::Hard, None)?;
+    Ok(())
+}
+
+fn verify_git_tree(
+    repo: &Repository,
+    target: &git2::Object,
+    trusted_keys: &[String],
+) -> Result<(), RemoteError> {
+    let tree = target.peel_to_tree()?;
+
+    let mut contents = BTreeMap::new();
+    let mut failure = None;
+    tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+        if entry.kind() != Some(ObjectType::Blob) {
+            return TreeWalkResult::Ok;
+        }
+        match repo.find_blob(entry.id()) {
+            Ok(blob) => {
+                let path = format!("{root}{}", entry.name().unwrap_or_default());
+                contents.insert(path, blob.content().to_vec());
+                TreeWalkResult::Ok
+            }
+            Err(e) => {
+                failure = Some(e);
+                TreeWalkResult::Abort
+            }
+        }
+    })?;
+    if let Some(e) = failure {
+        return Err(e.into());
+    }
+
+    let index = contents
+        .get(REMOTE_INDEX_FILE)
+        .ok_or_else(|| SignatureError::Unsigned(REMOTE_INDEX_FILE.to_string()))?;
+    let signature = contents
+        .get(&format!("{REMOTE_INDEX_FILE}.sig"))
+        .map(Vec::as_slice);
+
+    verify_index(index, signature, trusted_keys)?.verify_contents(&contents)?;
+    Ok(())
+}
+
+fn sync_http(url: &str, dest: &Path, trusted_keys: Option<&[String]>) -> Result<(), RemoteError> {
+    let base = url.trim_end_matches('/');
+
+    let raw_index = download(&format!("{base}/{REMOTE_INDEX_FILE}"))?;
+    let index: RemoteIndex = match trusted_keys {
+        Some(trusted_keys) => {
+            let signature = download(&format!("{base}/{REMOTE_INDEX_FILE}.sig")).ok();
+            verify_index(&raw_index, signature.as_deref(), trusted_keys)?
+        }
+        None => serde_json::from_slice(&raw_index)
+            .map_err(|e| RemoteError::InvalidIndex(e.to_string()))?,
+    };
+
+    // download everything before touching the previous mirror, so a failed
+    // sync leaves it intact
+    let parent = dest.parent().unwrap_or(Path::new("."));
+    fs::create_dir_all(parent)?;
+    let staging = tempfile::tempdir_in(parent)?;
+
+    let mut contents = BTreeMap::new();
+    for (name, releases) in &index.packages {
+        for (version, release) in releases {
+            let release_dir = PathBuf::from(checked_path(name)?).join(checked_path(version)?);
+
+            for file in std::iter::once(BUILD_FILE).chain(release.files.iter().map(String::as_str))
+            {
+                let relative = release_dir.join(checked_path(file)?);
+                let data = download(&format!("{base}/{}", relative.display()))?;
+                contents.insert(relative.to_string_lossy().to_string(), data);
+            }
+        }
+    }
+
+    if trusted_keys.is_some() {
+        index.verify_contents(&contents)?;
+    }
+
+    for (relative, data) in contents {
+        let target = staging.path().join(relative);
+        fs::create_dir_all(target.parent().unwrap())?;
+        fs::write(target, data)?;
+    }
+
+    if dest.exists() {
+        fs::remove_dir_all(dest)?;
+    }
+    fs::rename(staging.keep(), dest)?;
+    Ok(())
+}
+
+fn download(url: &str) -> Result<Vec<u8>, RemoteError> {
+    let response = ureq::get(url)
+        .call()
+        .map_err(|e| RemoteError::DownloadError {
+            url: url.to_string(),
+            reason: e.to_string(),
+        })?;
+
+    let mut data = Vec::new();
+    response.into_reader().read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// Rejects absolute paths and `..` so a remote cannot write outside its
+/// own directory.
+fn checked_path(path: &str) -> Result<&str, RemoteError> {
+    let valid = !path.is_empty()
+        && Path::new(path)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)));
+
+    if valid {
+        Ok(path)
+    } else {
+        Err(RemoteError::InvalidPath(path.to_string()))
+    }
+}
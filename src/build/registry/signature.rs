@@ -0,0 +1,113 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use tempfile::TempDir;
+use thiserror::Error;
+
+pub const SIGNATURE_NAMESPACE: &str = "reponere-registry";
+const PRINCIPAL: &str = "reponere";
+
+#[derive(Debug, Error)]
+pub enum SignatureError {
+    #[error("io error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("no trusted keys are configured")]
+    NoTrustedKeys,
+
+    #[error("{0} is not signed")]
+    Unsigned(String),
+
+    #[error("signature verification failed: {0}")]
+    VerificationFailed(String),
+
+    #[error("signing failed: {0}")]
+    SigningFailed(String),
+
+    #[error("{path} does not match the signed index")]
+    Tampered { path: String },
+}
+
+/// The detached signature file that belongs to `path`.
+pub fn signature_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".sig");
+    PathBuf::from(name)
+}
+
+/// Signs `path` with the ssh private key at `key`, writing `<path>.sig`.
+pub fn sign(path: &Path, key: &Path) -> Result<PathBuf, SignatureError> {
+    let signature = signature_path(path);
+    if signature.exists() {
+        fs::remove_file(&signature)?;
+    }
+
+    let output = Command::new("ssh-keygen")
+        .args(["-Y", "sign", "-n", SIGNATURE_NAMESPACE, "-f"])
+        .arg(key)
+        .arg(path)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(SignatureError::SigningFailed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(signature)
+}
+
+/// Checks that `signature` is a valid signature over `data` made by one of
+/// `trusted_keys`, given as ssh public key lines.
+pub fn verify(
+    data: &[u8],
+    signature: &[u8],
+    trusted_keys: &[String],
+) -> Result<(), SignatureError> {
+    if trusted_keys.is_empty() {
+        return Err(SignatureError::NoTrustedKeys);
+    }
+
+    let dir = TempDir::new()?;
+    let allowed_signers = dir.path().join("allowed_signers");
+    let signature_path = dir.path().join("signature");
+    let data_path = dir.path().join("data");
+
+    let signers: String = trusted_keys
+        .iter()
+        .map(|key| {
+            format!(
+                "{PRINCIPAL} namespaces=\"{SIGNATURE_NAMESPACE}\" {}\n",
+                key.trim()
+            )
+        })
+        .collect();
+    fs::write(&allowed_signers, signers)?;
+    fs::write(&signature_path, signature)?;
+    fs::write(&data_path, data)?;
+
+    let output = Command::new("ssh-keygen")
+        .args([
+            "-Y",
+            "verify",
+            "-n",
+            SIGNATURE_NAMESPACE,
+            "-I",
+            PRINCIPAL,
+            "-f",
+        ])
+        .arg(&allowed_signers)
+        .arg("-s")
+        .arg(&signature_path)
+        .stdin(fs::File::open(&data_path)?)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(SignatureError::VerificationFailed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,322 @@
+use std::{
+    fs,
+    io::{self, Cursor, Read},
+    path::{Component, Path, PathBuf},
+};
+
+use sha2::{Digest, Sha256};
+use tempfile::TempDir;
+use thiserror::Error;
+
+use crate::build::package::package::Source;
+
+#[derive(Debug, Error)]
+pub enum ArchiveSourceHandlerError {
+    #[error("Failed to access file system: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Failed to download archive: {0}")]
+    FailedDownloadingError(String),
+
+    #[error("Failed to extract archive: {0}")]
+    FailedExtractingError(String),
+
+    #[error("Checksum mismatch: expected {expected}, got {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+
+    #[error("Unsupported archive format: {0}")]
+    UnsupportedFormat(String),
+
+    #[error("Unsupported source type")]
+    Unsupported,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ArchiveFormat {
+    TarGz,
+    TarXz,
+    TarZst,
+    Zip,
+}
+
+impl ArchiveFormat {
+    pub fn from_url(url: &str) -> Option<Self> {
+        let path = url.split(['?', '#']).next().unwrap_or(url);
+
+        if path.ends_with(".tar.gz") || path.ends_with(".tgz") {
+            Some(ArchiveFormat::TarGz)
+        } else if path.ends_with(".tar.xz") || path.ends_with(".txz") {
+            Some(ArchiveFormat::TarXz)
+        } else if path.ends_with(".tar.zst") || path.ends_with(".tzst") {
+            Some(ArchiveFormat::TarZst)
+        } else if path.ends_with(".zip") {
+            Some(ArchiveFormat::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ArchiveSource<'a> {
+    pub url: &'a str,
+    pub sha256: Option<&'a str>,
+    pub blake3: Option<&'a str>,
+    pub strip_components: usize,
+    pub format: ArchiveFormat,
+}
+
+impl<'a> ArchiveSource<'a> {
+    pub fn from_source(source: &'a Source) -> Result<Self, ArchiveSourceHandlerError> {
+        if let Source::Archive {
+            url,
+            sha256,
+            blake3,
+            strip_components,
+        } = source
+        {
+            let format = ArchiveFormat::from_url(url)
+                .ok_or_else(|| ArchiveSourceHandlerError::UnsupportedFormat(url.clone()))?;
+
+            Ok(Self {
+                url: url.as_str(),
+                sha256: sha256.as_deref(),
+                blake3: blake3.as_deref(),
+                strip_components: strip_components.unwrap_or(0),
+                format,
+            })
+        } else {
+            Err(ArchiveSourceHandlerError::Unsupported)
+        }
+    }
+}
+
+pub struct ArchiveSourceHandler<'a> {
+    source: ArchiveSource<'a>,
+}
+
+impl<'a> ArchiveSourceHandler<'a> {
+    pub fn new(source: ArchiveSource<'a>) -> Self {
+        Self { source }
+    }
+
+    pub fn fetch(&self) -> Result<TempDir, ArchiveSourceHandlerError> {
+        let data = self.download()?;
+        self.verify_checksum(&data)?;
+
+        let dir = TempDir::new()?;
+        self.extract(&data, dir.path())?;
+
+        Ok(dir)
+    }
+
+    /// Returns the archive's sha256, downloading it when the recipe does not
+    /// pin one.
+    pub fn sha256(&self) -> Result<String, ArchiveSourceHandlerError> {
+        if let Some(sha256) = self.source.sha256 {
+            return Ok(sha256.to_ascii_lowercase());
+        }
+
+        let data = self.download()?;
+        self.verify_checksum(&data)?;
+        Ok(hex::encode(Sha256::digest(&data)))
+    }
+
+    fn download(&self) -> Result<Vec<u8>, ArchiveSourceHandlerError> {
+        if let Some(path) = self.source.url.strip_prefix("file://") {
+            return Ok(fs::read(path)?);
+        }
+
+        let response = ureq::get(self.source.url)
+            .call()
+            .map_err(|e| ArchiveSourceHandlerError::FailedDownloadingError(e.to_string()))?;
+
+        let mut data = Vec::new();
+        response.into_reader().read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    fn verify_checksum(&self, data: &[u8]) -> Result<(), ArchiveSourceHandlerError> {
+        if let Some(expected) = self.source.sha256 {
+            compare_checksum(expected, hex::encode(Sha256::digest(data)))?;
+        }
+        if let Some(expected) = self.source.blake3 {
+            compare_checksum(expected, blake3::hash(data).to_hex().to_string())?;
+        }
+        Ok(())
+    }
+
+    fn extract(&self, data: &[u8], dest: &Path) -> Result<(), ArchiveSourceHandlerError> {
+        match self.source.format {
+            ArchiveFormat::TarGz => self.unpack_tar(flate2::read::GzDecoder::new(data), dest),
+            ArchiveFormat::TarXz => self.unpack_tar(xz2::read::XzDecoder::new(data), dest),
+            ArchiveFormat::TarZst => self.unpack_tar(zstd::stream::Decoder::new(data)?, dest),
+            ArchiveFormat::Zip => self.unpack_zip(data, dest),
+        }
+    }
+
+    fn unpack_tar<R: Read>(&self, reader: R, dest: &Path) -> Result<(), ArchiveSourceHandlerError> {
+        let mut archive = tar::Archive::new(reader);
+        let root = dest.canonicalize()?;
+
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let path = entry.path()?.into_owned();
+
+            let Some(relative) = strip_path(&path, self.source.strip_components)? else {
+                continue;
+            };
+
+            let target = confine(&root, &relative)?;
+            if fs::symlink_metadata(&target).is_ok_and(|meta| !meta.is_dir()) {
+                fs::remove_file(&target)?;
+            }
+
+            match entry.header().entry_type() {
+                tar::EntryType::Symlink => {
+                    let link = entry.link_name()?.unwrap_or_default().into_owned();
+                    check_symlink(&relative, &link)?;
+                }
+                tar::EntryType::Link => {
+                    let link = entry.link_name()?.unwrap_or_default().into_owned();
+                    let source = strip_path(&link, self.source.strip_components)?
+                        .map(|source| root.join(source).canonicalize())
+                        .transpose()?
+                        .filter(|source| source.starts_with(&root))
+                        .ok_or_else(|| unsafe_link(&path, &link))?;
+                    fs::hard_link(source, &target)?;
+                    continue;
+                }
+                _ => {}
+            }
+            entry.unpack(&target)?;
+        }
+        Ok(())
+    }
+
+    fn unpack_zip(&self, data: &[u8], dest: &Path) -> Result<(), ArchiveSourceHandlerError> {
+        let mut archive = zip::ZipArchive::new(Cursor::new(data))
+            .map_err(|e| ArchiveSourceHandlerError::FailedExtractingError(e.to_string()))?;
+
+        for i in 0..archive.len() {
+            let mut file = archive
+                .by_index(i)
+                .map_err(|e| ArchiveSourceHandlerError::FailedExtractingError(e.to_string()))?;
+
+            let path = file.enclosed_name().ok_or_else(|| {
+                ArchiveSourceHandlerError::FailedExtractingError(format!(
+                    "unsafe path in archive: {}",
+                    file.name()
+                ))
+            })?;
+
+            let Some(relative) = strip_path(&path, self.source.strip_components)? else {
+                continue;
+            };
+
+            let target = dest.join(relative);
+            if file.is_dir() {
+                fs::create_dir_all(&target)?;
+                continue;
+            }
+
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut out = fs::File::create(&target)?;
+            io::copy(&mut file, &mut out)?;
+
+            #[cfg(unix)]
+            if let Some(mode) = file.unix_mode() {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(&target, fs::Permissions::from_mode(mode))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn compare_checksum(expected: &str, actual: String) -> Result<(), ArchiveSourceHandlerError> {
+    if !actual.eq_ignore_ascii_case(expected) {
+        return Err(ArchiveSourceHandlerError::ChecksumMismatch {
+            expected: expected.to_string(),
+            actual,
+        });
+    }
+    Ok(())
+}
+
+/// Creates the parent directories of `relative` under `root` and returns
+/// where the entry goes, refusing parents that resolve outside `root`
+/// through symlinks unpacked earlier.
+fn confine(root: &Path, relative: &Path) -> Result<PathBuf, ArchiveSourceHandlerError> {
+    let mut dir = root.to_path_buf();
+    for component in relative.parent().into_iter().flat_map(Path::components) {
+        dir.push(component);
+        if fs::symlink_metadata(&dir).is_err() {
+            fs::create_dir(&dir)?;
+        }
+        dir = dir.canonicalize()?;
+        if !dir.starts_with(root) {
+            return Err(ArchiveSourceHandlerError::FailedExtractingError(format!(
+                "unsafe path in archive: {}",
+                relative.display()
+            )));
+        }
+    }
+
+    let name = relative.file_name().ok_or_else(|| {
+        ArchiveSourceHandlerError::FailedExtractingError(format!(
+            "unsafe path in archive: {}",
+            relative.display()
+        ))
+    })?;
+    Ok(dir.join(name))
+}
+
+/// Rejects symlinks that are absolute or climb out of the unpacked tree.
+fn check_symlink(relative: &Path, link: &Path) -> Result<(), ArchiveSourceHandlerError> {
+    let mut depth = relative.components().count() - 1;
+    for component in link.components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir if depth > 0 => depth -= 1,
+            _ => return Err(unsafe_link(relative, link)),
+        }
+    }
+    Ok(())
+}
+
+fn unsafe_link(path: &Path, link: &Path) -> ArchiveSourceHandlerError {
+    ArchiveSourceHandlerError::FailedExtractingError(format!(
+        "unsafe link in archive: {} -> {}",
+        path.display(),
+        link.display()
+    ))
+}
+
+fn strip_path(
+    path: &Path,
+    strip_components: usize,
+) -> Result<Option<PathBuf>, ArchiveSourceHandlerError> {
+    let mut components = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => components.push(part),
+            Component::CurDir => {}
+            _ => {
+                return Err(ArchiveSourceHandlerError::FailedExtractingError(format!(
+                    "unsafe path in archive: {}",
+                    path.display()
+                )));
+            }
+        }
+    }
+
+    if components.len() <= strip_components {
+        return Ok(None);
+    }
+
+    Ok(Some(components[strip_components..].iter().collect()))
+}
@@ -0,0 +1,204 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use regex::Regex;
+use tempfile::TempDir;
+use thiserror::Error;
+use walkdir::WalkDir;
+
+use crate::build::package::package::Source;
+
+#[derive(Debug, Error)]
+pub enum LocalSourceHandlerError {
+    #[error("Failed to copy local source: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Failed to walk local source: {0}")]
+    FailedWalkingDirectoryError(#[from] walkdir::Error),
+
+    #[error("Local source is not a directory: {0}")]
+    NotADirectory(String),
+
+    #[error("Invalid ignore pattern: {0}")]
+    InvalidIgnorePattern(String),
+
+    #[error("Unsupported source type")]
+    Unsupported,
+}
+
+#[derive(Debug)]
+pub struct LocalSource<'a> {
+    pub path: &'a str,
+    pub ignore: Vec<Regex>,
+}
+
+impl<'a> LocalSource<'a> {
+    pub fn from_source(source: &'a Source) -> Result<Self, LocalSourceHandlerError> {
+        if let Source::Local { path, ignore } = source {
+            let ignore = ignore
+                .iter()
+                .flatten()
+                .map(|pattern| {
+                    glob_to_regex(pattern)
+                        .map_err(|_| LocalSourceHandlerError::InvalidIgnorePattern(pattern.clone()))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(Self {
+                path: path.as_str(),
+                ignore,
+            })
+        } else {
+            Err(LocalSourceHandlerError::Unsupported)
+        }
+    }
+
+    pub fn is_ignored(&self, relative: &Path) -> bool {
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        let file_name = relative.rsplit('/').next().unwrap_or(&relative);
+
+        self.ignore
+            .iter()
+            .any(|re| re.is_match(&relative) || re.is_match(file_name))
+    }
+}
+
+pub struct LocalSourceHandler<'a> {
+    source: LocalSource<'a>,
+    base_dir: PathBuf,
+}
+
+impl<'a> LocalSourceHandler<'a> {
+    pub fn new<P: Into<PathBuf>>(source: LocalSource<'a>, base_dir: P) -> Self {
+        Self {
+            source,
+            base_dir: base_dir.into(),
+        }
+    }
+
+    pub fn resolve_path(&self) -> PathBuf {
+        let path = match self.source.path.strip_prefix("~/") {
+            Some(rest) => dirs::home_dir()
+                .map(|home| home.join(rest))
+                .unwrap_or_else(|| PathBuf::from(self.source.path)),
+            None => PathBuf::from(self.source.path),
+        };
+
+        if path.is_absolute() {
+            path
+        } else {
+            self.base_dir.join(path)
+        }
+    }
+
+    pub fn fetch(&self) -> Result<TempDir, LocalSourceHandlerError> {
+        let root = self.resolve_path();
+        if !root.is_dir() {
+            return Err(LocalSourceHandlerError::NotADirectory(
+                root.display().to_string(),
+            ));
+        }
+
+        let dir = TempDir::new()?;
+
+        for entry in self.entries(&root) {
+            let entry = entry?;
+            let relative = entry.path().strip_prefix(&root).unwrap();
+            let target = dir.path().join(relative);
+            let file_type = entry.file_type();
+
+            if file_type.is_dir() {
+                fs::create_dir_all(&target)?;
+            } else if file_type.is_symlink() {
+                copy_symlink(entry.path(), &target)?;
+            } else {
+                fs::copy(entry.path(), &target)?;
+            }
+        }
+
+        Ok(dir)
+    }
+
+    /// blake3 over the path, type and contents of every entry that `fetch`
+    /// would copy, in a stable order.
+    pub fn blake3(&self) -> Result<String, LocalSourceHandlerError> {
+        let root = self.resolve_path();
+        if !root.is_dir() {
+            return Err(LocalSourceHandlerError::NotADirectory(
+                root.display().to_string(),
+            ));
+        }
+
+        let mut hasher = blake3::Hasher::new();
+        for entry in self.entries(&root) {
+            let entry = entry?;
+            let relative = entry.path().strip_prefix(&root).unwrap();
+            let file_type = entry.file_type();
+
+            hasher.update(relative.to_string_lossy().as_bytes());
+            if file_type.is_dir() {
+                hasher.update(b"\0dir\0");
+            } else if file_type.is_symlink() {
+                hasher.update(b"\0link\0");
+                hasher.update(fs::read_link(entry.path())?.to_string_lossy().as_bytes());
+            } else {
+                let contents = fs::read(entry.path())?;
+                hasher.update(b"\0file\0");
+                hasher.update(&(contents.len() as u64).to_le_bytes());
+                hasher.update(&contents);
+            }
+            hasher.update(b"\0");
+        }
+
+        Ok(hasher.finalize().to_hex().to_string())
+    }
+
+    fn entries<'r>(
+        &'r self,
+        root: &'r Path,
+    ) -> impl Iterator<Item = walkdir::Result<walkdir::DirEntry>> + 'r {
+        WalkDir::new(root)
+            .min_depth(1)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(move |e| {
+                e.path()
+                    .strip_prefix(root)
+                    .map(|relative| !self.source.is_ignored(relative))
+                    .unwrap_or(false)
+            })
+    }
+}
+
+#[cfg(unix)]
+fn copy_symlink(from: &Path, to: &Path) -> Result<(), std::io::Error> {
+    std::os::unix::fs::symlink(fs::read_link(from)?, to)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(from: &Path, to: &Path) -> Result<(), std::io::Error> {
+    fs::copy(from, to).map(|_| ())
+}
+
+fn glob_to_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+
+    let mut re = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                re.push_str(".*");
+            }
+            '*' => re.push_str("[^/]*"),
+            '?' => re.push_str("[^/]"),
+            c => re.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    re.push('$');
+
+    Regex::new(&re)
+}
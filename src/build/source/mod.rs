@@ -1 +1,3 @@
+pub mod archive_handler;
+pub mod local_handler;
 pub mod source_handler;
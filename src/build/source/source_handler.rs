@@ -1,8 +1,15 @@
-use git2::{Oid, Repository};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use git2::{FetchOptions, Object, ObjectType, Oid, Repository};
+use sha2::{Digest, Sha256};
 use tempfile::TempDir;
 use thiserror::Error;
 
-use crate::build::package::package::Source;
+use crate::build::package::package::{Source, Submodules};
 
 #[derive(Debug, Error)]
 pub enum GitSourceHandlerError {
@@ -12,11 +19,27 @@ pub enum GitSourceHandlerError {
     #[error("Failed to clone git repo: {0}")]
     FailedFetchingGitRepoError(#[from] git2::Error),
 
+    #[error("Failed to fetch git-lfs objects: {0}")]
+    FailedFetchingLfsObjectsError(String),
+
     #[error("Unsupported source type: {0}")]
     InvalidSpecifications(String),
 
     #[error("Unsupported source type")]
     Unsupported,
+
+    #[error("{reference} resolves to {actual}, expected {expected}")]
+    CommitMismatch {
+        reference: String,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("No signature found on {0}")]
+    MissingSignature(String),
+
+    #[error("Signature verification failed: {0}")]
+    SignatureVerificationFailed(String),
 }
 
 #[derive(Debug)]
@@ -25,6 +48,13 @@ pub struct GitSource<'a> {
     pub tag: Option<&'a str>,
     pub branch: Option<&'a str>,
     pub commit: Option<&'a str>,
+    pub expected_commit: Option<&'a str>,
+    pub verify_signature: bool,
+    /// GnuPG fingerprints allowed to sign. Without any, the key has to be
+    /// fully trusted in the keyring.
+    pub signing_keys: &'a [String],
+    pub submodules: Submodules,
+    pub lfs: bool,
 }
 
 impl<'a> GitSource<'a> {
@@ -34,6 +64,11 @@ impl<'a> GitSource<'a> {
             tag,
             branch,
             commit,
+            expected_commit,
+            verify_signature,
+            signing_keys,
+            submodules,
+            lfs,
         } = source
         {
             let choices = [commit.is_some(), tag.is_some(), branch.is_some()];
@@ -44,11 +79,22 @@ impl<'a> GitSource<'a> {
                 ));
             }
 
+            if expected_commit.is_some() && tag.is_none() {
+                return Err(GitSourceHandlerError::InvalidSpecifications(
+                    "expected_commit may only be specified together with a tag".into(),
+                ));
+            }
+
             Ok(Self {
                 repo: repo.as_str(),
                 tag: tag.as_deref(),
                 branch: branch.as_deref(),
                 commit: commit.as_deref(),
+                expected_commit: expected_commit.as_deref(),
+                verify_signature: verify_signature.unwrap_or(false),
+                signing_keys: signing_keys.as_deref().unwrap_or_default(),
+                submodules: submodules.unwrap_or_default(),
+                lfs: lfs.unwrap_or(false),
             })
         } else {
             Err(GitSourceHandlerError::Unsupported)
@@ -58,14 +104,56 @@ impl<'a> GitSource<'a> {
 
 pub struct GitSourceHandler<'a> {
     source: GitSource<'a>,
+    cache_dir: Option<PathBuf>,
 }
 
 impl<'a> GitSourceHandler<'a> {
     pub fn new(source: GitSource<'a>) -> Self {
-        Self { source }
+        Self {
+            source,
+            cache_dir: None,
+        }
+    }
+
+    pub fn with_cache<P: Into<PathBuf>>(mut self, cache_dir: P) -> Self {
+        self.cache_dir = Some(cache_dir.into());
+        self
+    }
+
+    /// Mirrors are named after the sha256 of the URL, which is unique where
+    /// a sanitized URL would not be.
+    pub fn mirror_path(cache_dir: &Path, repo: &str) -> PathBuf {
+        let name = hex::encode(Sha256::digest(repo.trim_end_matches('/')));
+        cache_dir.join("git").join(name)
     }
 
     pub fn fetch(&self) -> Result<TempDir, GitSourceHandlerError> {
+        match &self.cache_dir {
+            Some(cache_dir) => self.fetch_cached(cache_dir),
+            None => self.fetch_clone(),
+        }
+    }
+
+    /// Resolves the configured commit, tag, branch or HEAD to a commit id
+    /// without checking anything out.
+    pub fn resolve_commit(&self) -> Result<String, GitSourceHandlerError> {
+        let scratch;
+        let cache_dir = match &self.cache_dir {
+            Some(cache_dir) => cache_dir.as_path(),
+            None => {
+                scratch = TempDir::new()?;
+                scratch.path()
+            }
+        };
+
+        let mirror = self.update_mirror(cache_dir)?;
+        let object = self.resolve_cached(&mirror)?;
+        self.verify_expected_commit(&object)?;
+
+        Ok(object.peel_to_commit()?.id().to_string())
+    }
+
+    fn fetch_clone(&self) -> Result<TempDir, GitSourceHandlerError> {
         let dir = TempDir::new()?;
 
         let repo = Repository::clone(self.source.repo, dir.path())?;
@@ -80,10 +168,356 @@ impl<'a> GitSourceHandler<'a> {
             (None, None, None) => repo.head()?.peel(git2::ObjectType::Commit)?,
         };
 
+        self.verify_expected_commit(&object)?;
+        if self.source.verify_signature {
+            self.verify_signature(&repo, &object)?;
+        }
+
         let mut checkout_opts = git2::build::CheckoutBuilder::new();
         checkout_opts.force();
         repo.checkout_tree(&object, Some(&mut checkout_opts))?;
 
+        self.finish_checkout(&repo)?;
         Ok(dir)
     }
+
+    fn fetch_cached(&self, cache_dir: &Path) -> Result<TempDir, GitSourceHandlerError> {
+        let mirror = self.update_mirror(cache_dir)?;
+        let object = self.resolve_cached(&mirror)?;
+
+        self.verify_expected_commit(&object)?;
+        if self.source.verify_signature {
+            self.verify_signature(&mirror, &object)?;
+        }
+
+        let dir = TempDir::new()?;
+        let repo = checkout_from_mirror(&mirror, &object, dir.path(), self.source.repo)?;
+
+        self.finish_checkout(&repo)?;
+        Ok(dir)
+    }
+
+    fn finish_checkout(&self, repo: &Repository) -> Result<(), GitSourceHandlerError> {
+        let mut workdirs = vec![repo.workdir().unwrap_or(repo.path()).to_path_buf()];
+
+        match self.source.submodules {
+            Submodules::Disabled => {}
+            Submodules::Enabled => update_submodules(repo, false, &mut workdirs)?,
+            Submodules::Recursive => update_submodules(repo, true, &mut workdirs)?,
+        }
+
+        if self.source.lfs && git_lfs_available() {
+            for workdir in &workdirs {
+                pull_lfs_objects(workdir)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn update_mirror(&self, cache_dir: &Path) -> Result<Repository, GitSourceHandlerError> {
+        let path = Self::mirror_path(cache_dir, self.source.repo);
+
+        let mirror = if path.exists() {
+            Repository::open_bare(&path)?
+        } else {
+            fs::create_dir_all(&path)?;
+            let mirror = Repository::init_bare(&path)?;
+            mirror.remote("origin", self.source.repo)?;
+            mirror
+        };
+
+        let pinned = self.source.commit.is_some() || self.source.tag.is_some();
+        if pinned && self.resolve_cached(&mirror).is_ok() {
+            return Ok(mirror);
+        }
+
+        match self.fetch_into_mirror(&mirror, pinned) {
+            Ok(()) => Ok(mirror),
+            // offline, but branches and HEAD can still be served from the cache
+            Err(_) if self.resolve_cached(&mirror).is_ok() => Ok(mirror),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn fetch_into_mirror(&self, mirror: &Repository, shallow: bool) -> Result<(), git2::Error> {
+        let refspecs = match (self.source.commit, self.source.tag, self.source.branch) {
+            (Some(commit), _, _) => vec![commit.to_string()],
+            (None, Some(tag), _) => vec![format!("+refs/tags/{tag}:refs/tags/{tag}")],
+            (None, None, Some(branch)) => {
+                vec![format!("+refs/heads/{branch}:refs/heads/{branch}")]
+            }
+            (None, None, None) => vec!["+HEAD:refs/remotes/origin/HEAD".to_string()],
+        };
+
+        // the local transport does not support shallow fetches
+        let local = !self.source.repo.contains("://") || self.source.repo.starts_with("file://");
+
+        if shallow && !local {
+            let mut opts = FetchOptions::new();
+            opts.depth(1);
+            let mut remote = mirror.find_remote("origin")?;
+            if remote.fetch(&refspecs, Some(&mut opts), None).is_ok() {
+                return Ok(());
+            }
+        }
+
+        // not every server supports shallow fetches or fetching a bare commit
+        // id, so fall back to fetching full history
+        let refspecs = match self.source.commit {
+            Some(_) => vec![
+                "+refs/heads/*:refs/heads/*".to_string(),
+                "+refs/tags/*:refs/tags/*".to_string(),
+            ],
+            None => refspecs,
+        };
+        let mut remote = mirror.find_remote("origin")?;
+        remote.fetch(&refspecs, None, None)
+    }
+
+    fn resolve_cached<'r>(&self, repo: &'r Repository) -> Result<Object<'r>, git2::Error> {
+        match (self.source.commit, self.source.tag, self.source.branch) {
+            (Some(commit), _, _) => repo.find_object(Oid::from_str(commit)?, None),
+            (None, Some(tag), _) => repo.revparse_single(&format!("refs/tags/{tag}")),
+            (None, None, Some(branch)) => repo.revparse_single(&format!("refs/heads/{branch}")),
+            (None, None, None) => repo.revparse_single("refs/remotes/origin/HEAD"),
+        }
+    }
+
+    fn verify_expected_commit(&self, object: &Object) -> Result<(), GitSourceHandlerError> {
+        let (Some(tag), Some(expected)) = (self.source.tag, self.source.expected_commit) else {
+            return Ok(());
+        };
+
+        let actual = object.peel_to_commit()?.id().to_string();
+        let expected = expected.to_ascii_lowercase();
+
+        if expected.len() < 7 || !actual.starts_with(&expected) {
+            return Err(GitSourceHandlerError::CommitMismatch {
+                reference: format!("refs/tags/{tag}"),
+                expected,
+                actual,
+            });
+        }
+        Ok(())
+    }
+
+    fn verify_signature(
+        &self,
+        repo: &Repository,
+        object: &Object,
+    ) -> Result<(), GitSourceHandlerError> {
+        let (signature, signed_data) = match object.kind() {
+            Some(ObjectType::Tag) => {
+                let odb = repo.odb()?;
+                let raw = odb.read(object.id())?;
+                split_tag_signature(raw.data()).ok_or_else(|| {
+                    GitSourceHandlerError::MissingSignature(object.id().to_string())
+                })?
+            }
+            _ => {
+                let commit = object.peel_to_commit()?;
+                let (signature, signed_data) =
+                    repo.extract_signature(&commit.id(), None).map_err(|_| {
+                        GitSourceHandlerError::MissingSignature(commit.id().to_string())
+                    })?;
+                (signature.to_vec(), signed_data.to_vec())
+            }
+        };
+
+        verify_detached_signature(&signature, &signed_data, self.source.signing_keys)
+    }
+}
+
+fn checkout_from_mirror(
+    mirror: &Repository,
+    object: &Object,
+    dest: &Path,
+    url: &str,
+) -> Result<Repository, GitSourceHandlerError> {
+    // origin lets relative submodule urls and git-lfs resolve against upstream
+    Repository::init(dest)?.remote("origin", url)?;
+
+    let git_dir = dest.join(".git");
+    fs::create_dir_all(git_dir.join("objects/info"))?;
+    fs::write(
+        git_dir.join("objects/info/alternates"),
+        format!("{}\n", mirror.path().join("objects").display()),
+    )?;
+    if let Ok(shallow) = fs::read(mirror.path().join("shallow")) {
+        fs::write(git_dir.join("shallow"), shallow)?;
+    }
+
+    let repo = Repository::open(dest)?;
+    let object = repo.find_object(object.id(), None)?;
+    repo.set_head_detached(object.peel_to_commit()?.id())?;
+
+    let mut checkout_opts = git2::build::CheckoutBuilder::new();
+    checkout_opts.force();
+    repo.checkout_tree(&object, Some(&mut checkout_opts))?;
+    drop(object);
+
+    Ok(repo)
+}
+
+fn update_submodules(
+    repo: &Repository,
+    recursive: bool,
+    workdirs: &mut Vec<PathBuf>,
+) -> Result<(), GitSourceHandlerError> {
+    for mut submodule in repo.submodules()? {
+        submodule.update(true, None)?;
+
+        let submodule_repo = submodule.open()?;
+        if let Some(workdir) = submodule_repo.workdir() {
+            workdirs.push(workdir.to_path_buf());
+        }
+        if recursive {
+            update_submodules(&submodule_repo, true, workdirs)?;
+        }
+    }
+    Ok(())
+}
+
+fn git_lfs_available() -> bool {
+    Command::new("git-lfs")
+        .arg("version")
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+fn pull_lfs_objects(workdir: &Path) -> Result<(), GitSourceHandlerError> {
+    let output = Command::new("git")
+        .args(["lfs", "pull"])
+        .current_dir(workdir)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(GitSourceHandlerError::FailedFetchingLfsObjectsError(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(())
+}
+
+const PGP_SIGNATURE_HEADER: &[u8] = b"-----BEGIN PGP SIGNATURE-----";
+const SSH_SIGNATURE_HEADER: &[u8] = b"-----BEGIN SSH SIGNATURE-----";
+
+fn split_tag_signature(data: &[u8]) -> Option<(Vec<u8>, Vec<u8>)> {
+    let start = [PGP_SIGNATURE_HEADER, SSH_SIGNATURE_HEADER]
+        .iter()
+        .filter_map(|header| data.windows(header.len()).position(|w| w == *header))
+        .min()?;
+
+    Some((data[start..].to_vec(), data[..start].to_vec()))
+}
+
+fn verify_detached_signature(
+    signature: &[u8],
+    data: &[u8],
+    signing_keys: &[String],
+) -> Result<(), GitSourceHandlerError> {
+    let dir = TempDir::new()?;
+    let signature_path = dir.path().join("signature");
+    let data_path = dir.path().join("data");
+    fs::write(&signature_path, signature)?;
+    fs::write(&data_path, data)?;
+
+    let output = if signature.starts_with(SSH_SIGNATURE_HEADER) {
+        let allowed_signers = ssh_allowed_signers()?;
+        let principal = ssh_find_principal(&allowed_signers, &signature_path)?;
+
+        Command::new("ssh-keygen")
+            .args(["-Y", "verify", "-n", "git", "-f"])
+            .arg(&allowed_signers)
+            .arg("-I")
+            .arg(&principal)
+            .arg("-s")
+            .arg(&signature_path)
+            .stdin(fs::File::open(&data_path)?)
+            .output()?
+    } else {
+        Command::new("gpg")
+            .args(["--status-fd", "1", "--verify"])
+            .arg(&signature_path)
+            .arg(&data_path)
+            .output()?
+    };
+
+    if !output.status.success() {
+        return Err(GitSourceHandlerError::SignatureVerificationFailed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    if !signature.starts_with(SSH_SIGNATURE_HEADER)
+        && gpg_trusted_signer(&String::from_utf8_lossy(&output.stdout), signing_keys).is_none()
+    {
+        return Err(GitSourceHandlerError::SignatureVerificationFailed(
+            "signed by a key that is not trusted or not listed in signing_keys".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Finds the signer in `gpg --status-fd` output. A good signature only
+/// counts when its key, or that key's primary key, is one of
+/// `signing_keys`, or, when the recipe lists none, when the key is fully
+/// trusted in the keyring.
+pub fn gpg_trusted_signer(status: &str, signing_keys: &[String]) -> Option<String> {
+    let fields = |keyword: &str| {
+        status
+            .lines()
+            .filter_map(|line| line.strip_prefix("[GNUPG:] "))
+            .map(|line| line.split_whitespace().collect::<Vec<_>>())
+            .find(|fields| fields.first() == Some(&keyword))
+    };
+
+    let valid = fields("VALIDSIG")?;
+    let fingerprint = valid.get(1)?.to_string();
+    let primary = valid.get(10).copied().unwrap_or(&fingerprint);
+
+    let accepted = if signing_keys.is_empty() {
+        fields("TRUST_FULLY").is_some() || fields("TRUST_ULTIMATE").is_some()
+    } else {
+        let normalize = |key: &str| key.replace(' ', "").to_ascii_uppercase();
+        signing_keys
+            .iter()
+            .map(|key| normalize(key))
+            .any(|key| key == normalize(&fingerprint) || key == normalize(primary))
+    };
+
+    accepted.then_some(fingerprint)
+}
+
+fn ssh_allowed_signers() -> Result<PathBuf, GitSourceHandlerError> {
+    git2::Config::open_default()?
+        .get_path("gpg.ssh.allowedSignersFile")
+        .map_err(|_| {
+            GitSourceHandlerError::SignatureVerificationFailed(
+                "gpg.ssh.allowedSignersFile is not configured".into(),
+            )
+        })
+}
+
+fn ssh_find_principal(
+    allowed_signers: &Path,
+    signature_path: &Path,
+) -> Result<String, GitSourceHandlerError> {
+    let output = Command::new("ssh-keygen")
+        .args(["-Y", "find-principals", "-f"])
+        .arg(allowed_signers)
+        .arg("-s")
+        .arg(signature_path)
+        .output()?;
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .filter(|_| output.status.success())
+        .map(|principal| principal.trim().to_string())
+        .ok_or_else(|| {
+            GitSourceHandlerError::SignatureVerificationFailed(
+                "no trusted principal matches the ssh signature".into(),
+            )
+        })
 }
@@ -0,0 +1,187 @@
+use std::{
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use tempfile::TempDir;
+use thiserror::Error;
+use walkdir::WalkDir;
+
+use crate::{build::package::package::Manifest, handlers::events::InstallEvent};
+
+#[derive(Debug, Error)]
+pub enum StagingHandlerError {
+    #[error("Failed to install staged files: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Failed to walk staging directory: {0}")]
+    FailedWalkingDirectoryError(#[from] walkdir::Error),
+}
+
+pub struct StagingHandler {
+    staging_dir: TempDir,
+    root: PathBuf,
+}
+
+impl StagingHandler {
+    pub fn new<P: Into<PathBuf>>(root: P) -> Result<Self, StagingHandlerError> {
+        Ok(StagingHandler {
+            staging_dir: TempDir::new()?,
+            root: root.into(),
+        })
+    }
+
+    pub fn from_archive<P: Into<PathBuf>>(
+        root: P,
+        archive: &Path,
+    ) -> Result<Self, StagingHandlerError> {
+        let staging = StagingHandler::new(root)?;
+
+        let decoder = GzDecoder::new(fs::File::open(archive)?);
+        let mut archive = tar::Archive::new(decoder);
+        archive.set_preserve_permissions(true);
+        archive.unpack(staging.path())?;
+
+        Ok(staging)
+    }
+
+    pub fn archive(&self, dest: &Path) -> Result<(), StagingHandlerError> {
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let encoder = GzEncoder::new(fs::File::create(dest)?, Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        builder.follow_symlinks(false);
+        builder.append_dir_all(".", self.path())?;
+        builder.into_inner()?.finish()?;
+
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        self.staging_dir.path()
+    }
+
+    pub fn staged_files(&self) -> Result<Vec<String>, StagingHandlerError> {
+        let mut files = Vec::new();
+
+        for entry in WalkDir::new(self.path()).min_depth(1).sort_by_file_name() {
+            let entry = entry?;
+            if entry.file_type().is_dir() {
+                continue;
+            }
+            let relative = entry.path().strip_prefix(self.path()).unwrap();
+            files.push(path_string(&self.root.join(relative)));
+        }
+
+        Ok(files)
+    }
+
+    /// Copies every staged file next to its target under a temporary name
+    /// and only renames them into place once all copies succeeded, so a
+    /// failed install leaves the files under `root` as they were.
+    pub fn install<F: FnMut(InstallEvent)>(
+        &self,
+        progress: &mut F,
+    ) -> Result<Manifest, StagingHandlerError> {
+        let mut manifest = Manifest::default();
+        let mut copied = Vec::new();
+
+        if let Err(e) = self.copy_staged(&mut manifest, &mut copied, progress) {
+            for (temporary, _) in &copied {
+                let _ = fs::remove_file(temporary);
+            }
+            for dir in manifest.directories.iter().rev() {
+                let _ = fs::remove_dir(dir);
+            }
+            return Err(e);
+        }
+
+        for (temporary, target) in copied {
+            fs::rename(&temporary, &target)?;
+            manifest.files.push(path_string(&target));
+        }
+
+        Ok(manifest)
+    }
+
+    fn copy_staged<F: FnMut(InstallEvent)>(
+        &self,
+        manifest: &mut Manifest,
+        copied: &mut Vec<(PathBuf, PathBuf)>,
+        progress: &mut F,
+    ) -> Result<(), StagingHandlerError> {
+        for entry in WalkDir::new(self.path()).min_depth(1).sort_by_file_name() {
+            let entry = entry?;
+            let relative = entry.path().strip_prefix(self.path()).unwrap();
+            let target = self.root.join(relative);
+            let file_type = entry.file_type();
+
+            if file_type.is_dir() {
+                if !target.exists() {
+                    fs::create_dir(&target)?;
+                    manifest.directories.push(path_string(&target));
+                }
+                continue;
+            }
+
+            progress(InstallEvent::InstallingFile {
+                path: path_string(&target),
+            });
+
+            let mut name = OsString::from(".");
+            name.push(entry.file_name());
+            name.push(".reponere-new");
+            let temporary = target.with_file_name(name);
+
+            if temporary.is_symlink() || temporary.is_file() {
+                fs::remove_file(&temporary)?;
+            }
+            if file_type.is_symlink() {
+                copy_symlink(entry.path(), &temporary)?;
+            } else {
+                fs::copy(entry.path(), &temporary)?;
+            }
+            copied.push((temporary, target));
+        }
+
+        Ok(())
+    }
+}
+
+pub fn remove_manifest_files(manifest: &Manifest) -> Result<(), std::io::Error> {
+    for file in &manifest.files {
+        match fs::remove_file(file) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+    }
+
+    let mut directories: Vec<&String> = manifest.directories.iter().collect();
+    directories.sort_by_key(|dir| std::cmp::Reverse(Path::new(dir).components().count()));
+
+    for dir in directories {
+        let path = Path::new(dir);
+        if path.is_dir() && fs::read_dir(path)?.next().is_none() {
+            fs::remove_dir(path)?;
+        }
+    }
+    Ok(())
+}
+
+fn path_string(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+#[cfg(unix)]
+fn copy_symlink(from: &Path, to: &Path) -> Result<(), std::io::Error> {
+    std::os::unix::fs::symlink(fs::read_link(from)?, to)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(from: &Path, to: &Path) -> Result<(), std::io::Error> {
+    fs::copy(from, to).map(|_| ())
+}
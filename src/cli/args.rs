@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use clap::{Parser, Subcommand};
 
 #[derive(Parser, Debug)]
@@ -15,13 +17,56 @@ pub struct Arg {
 #[derive(Subcommand, Debug)]
 pub enum SubArgs {
     Install {
+        #[arg(required_unless_present = "locked")]
+        packages: Vec<String>,
+
+        #[arg(long)]
+        overwrite: bool,
+
+        #[arg(long)]
+        locked: bool,
+
+        #[arg(long, default_value = "reponere.lock")]
+        lockfile: PathBuf,
+    },
+    Lock {
         #[arg(required = true)]
         packages: Vec<String>,
+
+        #[arg(long, default_value = "reponere.lock")]
+        lockfile: PathBuf,
     },
     Uninstall {
         #[arg(required = true)]
         packages: Vec<String>,
     },
+    Upgrade {
+        #[arg(required = false)]
+        packages: Vec<String>,
+
+        #[arg(long)]
+        overwrite: bool,
+    },
+    Rollback {
+        #[arg(required = true)]
+        package: String,
+
+        #[arg(long)]
+        to: Option<String>,
+
+        #[arg(long)]
+        overwrite: bool,
+    },
+    Apply {
+        #[arg(required = true)]
+        file: PathBuf,
+
+        #[arg(long)]
+        prune: bool,
+
+        #[arg(long)]
+        overwrite: bool,
+    },
     Sync,
     List {
         #[arg(required = false)]
@@ -34,4 +79,67 @@ pub enum SubArgs {
         #[arg(required = true)]
         package: String,
     },
+    Search {
+        #[arg(required = true)]
+        query: String,
+
+        #[arg(long)]
+        regex: bool,
+    },
+    Owns {
+        #[arg(required = true)]
+        path: String,
+    },
+    Log {
+        #[arg(required = true)]
+        package: String,
+
+        #[arg(long)]
+        tail: Option<usize>,
+    },
+    Cache {
+        #[command(subcommand)]
+        action: CacheArgs,
+    },
+    Registry {
+        #[command(subcommand)]
+        action: RegistryArgs,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CacheArgs {
+    Clean,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RegistryArgs {
+    New {
+        name: String,
+        version: String,
+
+        #[arg(long)]
+        repo: String,
+
+        #[arg(long, default_value = ".")]
+        dir: PathBuf,
+    },
+    Lint {
+        #[arg(default_value = ".")]
+        dir: PathBuf,
+    },
+    Bump {
+        name: String,
+        version: String,
+
+        #[arg(long, default_value = ".")]
+        dir: PathBuf,
+    },
+    Sign {
+        #[arg(default_value = ".")]
+        dir: PathBuf,
+
+        #[arg(long)]
+        key: PathBuf,
+    },
 }
@@ -1,14 +1,22 @@
+use std::path::Path;
+
 use crate::{
-    build::registry::registry_handler::Registry,
+    build::{
+        desired_state::desired_state::DesiredState, lockfile::lockfile::Lockfile,
+        package_manager::manager::PackageManager,
+    },
     cli::{
-        args::{Arg, SubArgs},
+        args::{Arg, CacheArgs, RegistryArgs, SubArgs},
         presenter::Presenter,
     },
     handlers::{
-        install_handler::{self, InstallResult},
+        apply_handler, cache_handler,
+        install_handler::{self, FileConflict, InstallError, InstallOptions, InstallResult},
         list_handler::{self},
-        show_handler,
+        lock_handler, log_handler, owns_handler, registry_handler, rollback_handler,
+        search_handler, show_handler, sync_handler,
         uninstall_handler::{self, UninstallError, UninstallPlan},
+        upgrade_handler,
     },
     util::context::Context,
 };
@@ -19,12 +27,44 @@ pub fn run(ctx: &mut Context) {
     let args = Arg::parse();
 
     match args.sub {
-        SubArgs::Install { packages } => {
-            install(ctx, packages);
+        SubArgs::Install {
+            packages,
+            overwrite,
+            locked,
+            lockfile,
+        } => {
+            if locked {
+                install_locked(ctx, packages, overwrite, &lockfile);
+            } else {
+                install(ctx, packages, overwrite, None);
+            }
+        }
+        SubArgs::Lock { packages, lockfile } => {
+            lock(ctx, &packages, &lockfile);
         }
         SubArgs::Uninstall { packages } => {
             uninstall(ctx, packages);
         }
+        SubArgs::Upgrade {
+            packages,
+            overwrite,
+        } => {
+            upgrade(ctx, packages, overwrite);
+        }
+        SubArgs::Rollback {
+            package,
+            to,
+            overwrite,
+        } => {
+            rollback(ctx, &package, to.as_deref(), overwrite);
+        }
+        SubArgs::Apply {
+            file,
+            prune,
+            overwrite,
+        } => {
+            apply(ctx, &file, prune, overwrite);
+        }
         SubArgs::Sync => {
             sync(ctx);
         }
@@ -37,17 +77,76 @@ pub fn run(ctx: &mut Context) {
         SubArgs::Show { package } => {
             show(ctx, &package);
         }
-        _ => todo!(),
+        SubArgs::Search { query, regex } => {
+            search(ctx, &query, regex);
+        }
+        SubArgs::Owns { path } => {
+            owns(ctx, &path);
+        }
+        SubArgs::Log { package, tail } => {
+            log(ctx, &package, tail);
+        }
+        SubArgs::Cache { action } => match action {
+            CacheArgs::Clean => {
+                cache_clean(ctx);
+            }
+        },
+        SubArgs::Registry { action } => match action {
+            RegistryArgs::New {
+                name,
+                version,
+                repo,
+                dir,
+            } => {
+                registry_new(&dir, &name, &version, &repo);
+            }
+            RegistryArgs::Lint { dir } => {
+                registry_lint(&dir);
+            }
+            RegistryArgs::Bump { name, version, dir } => {
+                registry_bump(&dir, &name, &version);
+            }
+            RegistryArgs::Sign { dir, key } => {
+                registry_sign(&dir, &key);
+            }
+        },
     }
 }
 
-fn install(ctx: &mut Context, packages: Vec<String>) {
+fn install(ctx: &mut Context, packages: Vec<String>, overwrite: bool, lockfile: Option<&Lockfile>) {
     let mut presenter = |event| Presenter::display(&event);
 
+    // the solution shown here is the one that gets installed
+    let solution = match lockfile {
+        Some(_) => None,
+        None => {
+            match install_handler::resolve_requests(&ctx.registry, &ctx.tracker, &packages, &[]) {
+                Ok(solution) => Some(solution),
+                Err(e) => {
+                    println!("==> Cannot install the requested packages: {e}");
+                    return;
+                }
+            }
+        }
+    };
+    let options = InstallOptions {
+        force: false,
+        overwrite,
+        lockfile,
+        solution: solution.as_deref(),
+    };
+
+    let listed = match &solution {
+        Some(solution) => solution
+            .iter()
+            .map(|p| format!("{} {}", p.name, p.version))
+            .collect(),
+        None => packages.clone(),
+    };
     if !Confirm::new()
         .with_prompt(format!(
             "Do you want to install the following packages?\n - {}",
-            packages.join("\n - ")
+            listed.join("\n - ")
         ))
         .interact()
         .unwrap()
@@ -56,18 +155,36 @@ fn install(ctx: &mut Context, packages: Vec<String>) {
         return;
     }
 
+    let mut confirm_overwrite = |conflicts: &[FileConflict]| {
+        println!("==> Installing would overwrite existing files:");
+        for conflict in conflicts {
+            println!(" - {conflict}");
+        }
+
+        Confirm::new()
+            .with_prompt("Overwrite these files?")
+            .interact()
+            .unwrap()
+    };
+
     for package in packages {
-        match install_handler::run(
+        match install_handler::run_confirming(
+            &ctx.config,
             &ctx.registry,
             &mut ctx.tracker,
             &package,
-            false,
+            options,
+            &mut confirm_overwrite,
             &mut presenter,
         ) {
             Ok(InstallResult::Installed) => {
                 println!("==> Installed {package}");
             }
 
+            Err(InstallError::FileConflicts(_)) => {
+                println!("==> Skipped {package}");
+            }
+
             Ok(InstallResult::AlreadyInstalled) => {
                 let reinstall = Confirm::new()
                     .with_prompt("Package already installed. Reinstall?")
@@ -78,11 +195,16 @@ fn install(ctx: &mut Context, packages: Vec<String>) {
                     continue;
                 }
 
-                match install_handler::run(
+                match install_handler::run_confirming(
+                    &ctx.config,
                     &ctx.registry,
                     &mut ctx.tracker,
                     &package,
-                    true,
+                    InstallOptions {
+                        force: true,
+                        ..options
+                    },
+                    &mut confirm_overwrite,
                     &mut presenter,
                 ) {
                     Ok(_) => println!("==> Reinstalled {package}"),
@@ -100,6 +222,50 @@ fn install(ctx: &mut Context, packages: Vec<String>) {
         .unwrap();
 }
 
+fn install_locked(ctx: &mut Context, packages: Vec<String>, overwrite: bool, path: &Path) {
+    let lockfile = match Lockfile::load(path) {
+        Ok(lockfile) => lockfile,
+        Err(e) => {
+            println!("==> Failed to read {}: {e}", path.display());
+            return;
+        }
+    };
+
+    let packages = if packages.is_empty() {
+        lockfile.packages.iter().map(|p| p.name.clone()).collect()
+    } else {
+        packages
+    };
+
+    install(ctx, packages, overwrite, Some(&lockfile));
+}
+
+fn lock(ctx: &Context, packages: &[String], path: &Path) {
+    let mut presenter = |event| Presenter::display(&event);
+
+    let package_manager = match PackageManager::get_package_manager(false) {
+        Ok(package_manager) => package_manager,
+        Err(e) => {
+            println!("==> something went wrong: {e}");
+            return;
+        }
+    };
+
+    match lock_handler::run(
+        &ctx.config,
+        &ctx.registry,
+        &ctx.tracker,
+        &package_manager,
+        packages,
+        &mut presenter,
+    )
+    .and_then(|lockfile| Ok(lockfile.save(path)?))
+    {
+        Ok(()) => println!("==> Wrote {}", path.display()),
+        Err(e) => println!("==> Failed to lock packages: {e}"),
+    }
+}
+
 fn uninstall(ctx: &mut Context, packages: Vec<String>) {
     let mut presenter = |event| Presenter::display(&event);
 
@@ -157,10 +323,141 @@ fn uninstall(ctx: &mut Context, packages: Vec<String>) {
     }
 }
 
+fn upgrade(ctx: &mut Context, packages: Vec<String>, overwrite: bool) {
+    let mut presenter = |event| Presenter::display(&event);
+
+    let candidates = match upgrade_handler::plan(&ctx.registry, &ctx.tracker, &packages) {
+        Ok(candidates) => candidates,
+        Err(e) => {
+            println!("==> something went wrong: {e}");
+            return;
+        }
+    };
+
+    if candidates.is_empty() {
+        println!("==> All packages are up-to-date");
+        return;
+    }
+
+    if !Confirm::new()
+        .with_prompt(format!(
+            "Do you want to upgrade the following packages?\n - {}",
+            candidates
+                .iter()
+                .map(|c| format!("{} {} -> {}", c.name, c.current, c.latest))
+                .collect::<Vec<_>>()
+                .join("\n - ")
+        ))
+        .interact()
+        .unwrap()
+    {
+        println!("==> Aborted upgrade");
+        return;
+    }
+
+    for candidate in &candidates {
+        if let Err(e) = upgrade_handler::execute(
+            &ctx.config,
+            &ctx.registry,
+            &mut ctx.tracker,
+            candidate,
+            overwrite,
+            &mut presenter,
+        ) {
+            println!(
+                "==> Failed to upgrade {}, keeping {}: {e}",
+                candidate.name, candidate.current
+            );
+        }
+        ctx.tracker
+            .save(ctx.config.packages_path.to_str().unwrap())
+            .unwrap();
+    }
+}
+
+fn rollback(ctx: &mut Context, package: &str, to: Option<&str>, overwrite: bool) {
+    let mut presenter = |event| Presenter::display(&event);
+
+    match rollback_handler::run(
+        &ctx.config,
+        &mut ctx.tracker,
+        package,
+        to,
+        overwrite,
+        &mut presenter,
+    ) {
+        Ok(_) => ctx
+            .tracker
+            .save(ctx.config.packages_path.to_str().unwrap())
+            .unwrap(),
+        Err(e) => println!("==> Failed to roll back {package}: {e}"),
+    }
+}
+
+fn apply(ctx: &mut Context, path: &Path, prune: bool, overwrite: bool) {
+    let mut presenter = |event| Presenter::display(&event);
+
+    let desired = match DesiredState::load(path) {
+        Ok(desired) => desired,
+        Err(e) => {
+            println!("==> Failed to read {}: {e}", path.display());
+            return;
+        }
+    };
+
+    let actions = match apply_handler::plan(&ctx.registry, &ctx.tracker, &desired, prune) {
+        Ok(actions) => actions,
+        Err(e) => {
+            println!("==> Cannot apply {}: {e}", path.display());
+            return;
+        }
+    };
+
+    if actions.is_empty() {
+        println!("==> Nothing to do");
+        return;
+    }
+
+    if !Confirm::new()
+        .with_prompt(format!(
+            "Do you want to apply the following changes?\n - {}",
+            actions
+                .iter()
+                .map(|a| a.to_string())
+                .collect::<Vec<_>>()
+                .join("\n - ")
+        ))
+        .interact()
+        .unwrap()
+    {
+        println!("==> Aborted apply");
+        return;
+    }
+
+    for action in &actions {
+        if let Err(e) = apply_handler::execute(
+            &ctx.config,
+            &ctx.registry,
+            &mut ctx.tracker,
+            action,
+            overwrite,
+            &mut presenter,
+        ) {
+            println!("==> Failed to {action}: {e}");
+        }
+        ctx.tracker
+            .save(ctx.config.packages_path.to_str().unwrap())
+            .unwrap();
+    }
+}
+
 fn sync(ctx: &mut Context) {
-    ctx.registry =
-        Registry::resync_from_directory_and_save(&ctx.config.index_path, &ctx.config.registry_path);
-    println!("==> Registry synced");
+    let mut presenter = |event| Presenter::display(&event);
+
+    match sync_handler::run(&ctx.config, &mut presenter) {
+        Ok(registry) => ctx.registry = registry,
+        Err(e) => println!("==> something went wrong: {e}"),
+    }
 }
 
 fn list(ctx: &Context, packages: Vec<String>, available: bool) {
@@ -177,3 +474,72 @@ fn show(ctx: &Context, package_name: &str) {
 
     show_handler::run(ctx, package_name, &mut presenter);
 }
+
+fn search(ctx: &Context, query: &str, regex: bool) {
+    let mut presenter = |event| Presenter::display(&event);
+
+    if let Err(e) = search_handler::run(&ctx.registry, &ctx.tracker, query, regex, &mut presenter) {
+        println!("==> something went wrong: {e}");
+    }
+}
+
+fn owns(ctx: &Context, path: &str) {
+    let mut presenter = |event| Presenter::display(&event);
+
+    owns_handler::run(&ctx.tracker, path, &mut presenter);
+}
+
+fn log(ctx: &Context, package: &str, tail: Option<usize>) {
+    let mut presenter = |event| Presenter::display(&event);
+
+    if let Err(e) = log_handler::run(&ctx.config, package, tail, &mut presenter) {
+        println!("==> something went wrong: {e}");
+    }
+}
+
+fn cache_clean(ctx: &Context) {
+    let mut presenter = |event| Presenter::display(&event);
+
+    match cache_handler::clean(&ctx.config, &mut presenter) {
+        Ok(()) => (),
+        Err(e) => println!("==> something went wrong: {e}"),
+    }
+}
+
+fn registry_new(dir: &Path, name: &str, version: &str, repo: &str) {
+    let mut presenter = |event| Presenter::display(&event);
+
+    if let Err(e) = registry_handler::new(dir, name, version, repo, &mut presenter) {
+        println!("==> something went wrong: {e}");
+    }
+}
+
+fn registry_lint(dir: &Path) {
+    let mut presenter = |event| Presenter::display(&event);
+
+    match registry_handler::lint(dir, &mut presenter) {
+        Ok(0) => {}
+        Ok(_) => std::process::exit(1),
+        Err(e) => {
+            println!("==> something went wrong: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn registry_bump(dir: &Path, name: &str, version: &str) {
+    let mut presenter = |event| Presenter::display(&event);
+
+    if let Err(e) = registry_handler::bump(dir, name, version, &mut presenter) {
+        println!("==> something went wrong: {e}");
+    }
+}
+
+fn registry_sign(dir: &Path, key: &Path) {
+    let mut presenter = |event| Presenter::display(&event);
+
+    match registry_handler::sign(dir, key, &mut presenter) {
+        Ok(()) => (),
+        Err(e) => println!("==> something went wrong: {e}"),
+    }
+}
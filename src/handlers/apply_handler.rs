@@ -0,0 +1,173 @@
+use std::fmt;
+
+use crate::{
+    build::{
+        dependency_handler::version::{VersionError, is_newer},
+        desired_state::desired_state::DesiredState,
+        package_tracker::package_tracker::PackageTracker,
+        registry::registry_handler::Registry,
+    },
+    handlers::{
+        events::ApplyEvent,
+        install_handler::{self, InstallError, InstallOptions},
+        uninstall_handler::{self, UninstallError},
+    },
+    util::config::Config,
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ApplyError {
+    #[error("{0}")]
+    InstallError(#[from] InstallError),
+    #[error("{0}")]
+    UninstallError(#[from] UninstallError),
+    #[error("{0}")]
+    InvalidVersion(#[from] VersionError),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApplyAction {
+    Install {
+        name: String,
+        version: String,
+    },
+    Upgrade {
+        name: String,
+        from: String,
+        to: String,
+    },
+    Downgrade {
+        name: String,
+        from: String,
+        to: String,
+    },
+    Remove {
+        name: String,
+        version: String,
+    },
+}
+
+impl fmt::Display for ApplyAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApplyAction::Install { name, version } => write!(f, "install {name} {version}"),
+            ApplyAction::Upgrade { name, from, to } => write!(f, "upgrade {name} {from} -> {to}"),
+            ApplyAction::Downgrade { name, from, to } => {
+                write!(f, "downgrade {name} {from} -> {to}")
+            }
+            ApplyAction::Remove { name, version } => write!(f, "remove {name} {version}"),
+        }
+    }
+}
+
+pub fn plan(
+    reg: &Registry,
+    tracker: &PackageTracker,
+    desired: &DesiredState,
+    prune: bool,
+) -> Result<Vec<ApplyAction>, ApplyError> {
+    let requests: Vec<String> = desired
+        .packages
+        .iter()
+        .map(|(name, requirement)| format!("{name}@{requirement}"))
+        .collect();
+
+    // when pruning, anything not listed may be removed, so it must not
+    // constrain the solution; listed packages pull in what they still need
+    let removing: Vec<String> = if prune {
+        tracker
+            .get_packages()
+            .keys()
+            .filter(|name| !desired.packages.contains_key(*name))
+            .cloned()
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    // dependencies come before the packages that need them
+    let resolved = install_handler::resolve_requests(reg, tracker, &requests, &removing)?;
+
+    // removals go first, so their requirements are gone by the time the
+    // remaining packages are installed
+    let mut actions = Vec::new();
+    if prune {
+        actions = tracker
+            .get_packages()
+            .values()
+            .filter(|installed| !resolved.iter().any(|p| p.name == installed.name))
+            .map(|installed| ApplyAction::Remove {
+                name: installed.name.clone(),
+                version: installed.version.clone(),
+            })
+            .collect();
+        actions.sort_by_key(|action| action.to_string());
+    }
+
+    for package in &resolved {
+        let to = package.version.clone();
+        let name = package.name.clone();
+
+        let Some(installed) = tracker.get_package(&package.name) else {
+            actions.push(ApplyAction::Install { name, version: to });
+            continue;
+        };
+        let from = installed.version.clone();
+        if from == to {
+            continue;
+        }
+        if is_newer(&to, &from)? {
+            actions.push(ApplyAction::Upgrade { name, from, to });
+        } else {
+            actions.push(ApplyAction::Downgrade { name, from, to });
+        }
+    }
+
+    Ok(actions)
+}
+
+pub fn execute<F: FnMut(ApplyEvent)>(
+    config: &Config,
+    reg: &Registry,
+    tracker: &mut PackageTracker,
+    action: &ApplyAction,
+    overwrite: bool,
+    progress: &mut F,
+) -> Result<(), ApplyError> {
+    progress(ApplyEvent::Applying {
+        action: action.to_string(),
+    });
+
+    match action {
+        ApplyAction::Install { name, version }
+        | ApplyAction::Upgrade {
+            name, to: version, ..
+        }
+        | ApplyAction::Downgrade {
+            name, to: version, ..
+        } => {
+            install_handler::run(
+                config,
+                reg,
+                tracker,
+                &format!("{name}@=={version}"),
+                InstallOptions {
+                    force: true,
+                    overwrite,
+                    lockfile: None,
+                    solution: None,
+                },
+                &mut |event| progress(ApplyEvent::Install(event)),
+            )?;
+        }
+        ApplyAction::Remove { name, .. } => {
+            let plan = uninstall_handler::plan(tracker, name)?;
+            uninstall_handler::execute(tracker, plan, &mut |event| {
+                progress(ApplyEvent::Uninstall(event))
+            })?;
+        }
+    }
+
+    Ok(())
+}
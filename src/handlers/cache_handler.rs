@@ -0,0 +1,35 @@
+use std::fs;
+
+use crate::{handlers::events::CacheEvent, util::config::Config};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum CacheError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+pub fn clean<F: FnMut(CacheEvent)>(config: &Config, progress: &mut F) -> Result<(), CacheError> {
+    let mirrors = config.cache_path.join("git");
+
+    if !mirrors.exists() {
+        progress(CacheEvent::AlreadyClean);
+        return Ok(());
+    }
+
+    progress(CacheEvent::Cleaning {
+        path: config.cache_path.display().to_string(),
+    });
+
+    for entry in fs::read_dir(&mirrors)? {
+        let entry = entry?;
+        progress(CacheEvent::RemovingMirror {
+            name: entry.file_name().to_string_lossy().to_string(),
+        });
+        fs::remove_dir_all(entry.path())?;
+    }
+    fs::remove_dir(&mirrors)?;
+
+    progress(CacheEvent::Finished);
+    Ok(())
+}
@@ -0,0 +1,17 @@
+use crate::handlers::events::{InstallEvent, UninstallEvent, event::Event};
+
+pub enum ApplyEvent {
+    Applying { action: String },
+    Install(InstallEvent),
+    Uninstall(UninstallEvent),
+}
+
+impl Event for ApplyEvent {
+    fn message(&self) -> String {
+        match self {
+            ApplyEvent::Applying { action } => format!("==> Applying: {action}"),
+            ApplyEvent::Install(event) => event.message(),
+            ApplyEvent::Uninstall(event) => event.message(),
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use crate::handlers::events::event::Event;
+
+pub enum CacheEvent {
+    Cleaning { path: String },
+    RemovingMirror { name: String },
+    AlreadyClean,
+    Finished,
+}
+
+impl Event for CacheEvent {
+    fn message(&self) -> String {
+        match self {
+            CacheEvent::Cleaning { path } => format!("==> Cleaning source cache {path}"),
+            CacheEvent::RemovingMirror { name } => format!("-> removing {name}"),
+            CacheEvent::AlreadyClean => "==> Source cache is already empty".to_string(),
+            CacheEvent::Finished => "==> Finished".to_string(),
+        }
+    }
+}
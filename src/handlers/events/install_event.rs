@@ -6,9 +6,19 @@ pub enum InstallEvent {
     InstallingBuildDependencies { dependencies: Vec<String> },
     InstallingDependency { name: String },
     DependencyAlreadyInstalled { name: String },
+    InstallingReponereDependency { name: String, version: String },
     FetchingSource,
+    ApplyingPatches,
+    ApplyingPatch { name: String },
     BuildingSource,
+    Sandboxed { tool: String, network: bool },
+    BuildPhase { phase: String },
     BuildStep { step: String },
+    BuildOutput { line: String },
+    SavingBuild,
+    InstallingFiles,
+    InstallingFile { path: String },
+    RemovingStaleFiles,
     Cleanup,
     Finished,
 }
@@ -29,9 +39,30 @@ impl Event for InstallEvent {
             InstallEvent::DependencyAlreadyInstalled { name } => {
                 format!("-> dependency {name} already installed")
             }
+            InstallEvent::InstallingReponereDependency { name, version } => {
+                format!("==> Building dependency {name}@{version}")
+            }
             InstallEvent::FetchingSource => "==> Fetching source".to_string(),
+            InstallEvent::ApplyingPatches => "==> Applying patches".to_string(),
+            InstallEvent::ApplyingPatch { name } => format!("-> {name}"),
             InstallEvent::BuildingSource => "==> Building source".to_string(),
+            InstallEvent::Sandboxed {
+                tool,
+                network: false,
+            } => format!("-> building in a {tool} sandbox without network access"),
+            InstallEvent::Sandboxed {
+                tool,
+                network: true,
+            } => format!("-> building in a {tool} sandbox with network access"),
+            InstallEvent::BuildPhase { phase } => format!("-> {phase} phase"),
             InstallEvent::BuildStep { step } => format!("-> {step}"),
+            InstallEvent::BuildOutput { line } => format!("   {line}"),
+            InstallEvent::SavingBuild => "==> Saving build for rollback".to_string(),
+            InstallEvent::InstallingFiles => "==> Installing files".to_string(),
+            InstallEvent::InstallingFile { path } => format!("-> {path}"),
+            InstallEvent::RemovingStaleFiles => {
+                "==> Removing files left over from the previous install".to_string()
+            }
             InstallEvent::Cleanup => "==> Cleanup".to_string(),
             InstallEvent::Finished => "==> Finished".to_string(),
         }
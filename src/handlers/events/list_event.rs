@@ -2,22 +2,21 @@ use crate::handlers::events::event::Event;
 
 pub enum ListEvent {
     Available,
-    AvailablePackage(String, String),
+    AvailablePackage(String, String, Option<String>),
     Installed,
-    InstalledPackage(String, String),
+    InstalledPackage(String, String, Option<String>),
 }
 
 impl Event for ListEvent {
     fn message(&self) -> String {
         match self {
             ListEvent::Available => "==> Available packages:".to_string(),
-            ListEvent::AvailablePackage(name, version) => {
-                format!("-> {name}@{version}")
-            }
+            ListEvent::AvailablePackage(name, version, description)
+            | ListEvent::InstalledPackage(name, version, description) => match description {
+                Some(description) => format!("-> {name}@{version} - {description}"),
+                None => format!("-> {name}@{version}"),
+            },
             ListEvent::Installed => "==> Installed packages:".to_string(),
-            ListEvent::InstalledPackage(name, version) => {
-                format!("-> {name}@{version}")
-            }
         }
     }
 }
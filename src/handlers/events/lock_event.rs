@@ -0,0 +1,17 @@
+use crate::handlers::events::event::Event;
+
+pub enum LockEvent {
+    Resolving,
+    Locking { name: String, version: String },
+    Finished { count: usize },
+}
+
+impl Event for LockEvent {
+    fn message(&self) -> String {
+        match self {
+            LockEvent::Resolving => "==> Resolving packages".to_string(),
+            LockEvent::Locking { name, version } => format!("-> Locking {name}@{version}"),
+            LockEvent::Finished { count } => format!("==> Locked {count} packages"),
+        }
+    }
+}
@@ -0,0 +1,15 @@
+use crate::handlers::events::event::Event;
+
+pub enum LogEvent {
+    ShowingLog { name: String, path: String },
+    Line { line: String },
+}
+
+impl Event for LogEvent {
+    fn message(&self) -> String {
+        match self {
+            LogEvent::ShowingLog { name, path } => format!("==> Build log of {name}: {path}"),
+            LogEvent::Line { line } => line.clone(),
+        }
+    }
+}
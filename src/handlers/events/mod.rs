@@ -1,10 +1,30 @@
+pub mod apply_event;
+pub mod cache_event;
 pub mod event;
 pub mod install_event;
 pub mod list_event;
-pub mod uninstall_event;
+pub mod lock_event;
+pub mod log_event;
+pub mod owns_event;
+pub mod registry_event;
+pub mod rollback_event;
+pub mod search_event;
 pub mod show_event;
+pub mod sync_event;
+pub mod uninstall_event;
+pub mod upgrade_event;
 
+pub use apply_event::ApplyEvent;
+pub use cache_event::CacheEvent;
 pub use install_event::InstallEvent;
 pub use list_event::ListEvent;
-pub use uninstall_event::UninstallEvent;
+pub use lock_event::LockEvent;
+pub use log_event::LogEvent;
+pub use owns_event::OwnsEvent;
+pub use registry_event::RegistryEvent;
+pub use rollback_event::RollbackEvent;
+pub use search_event::SearchEvent;
 pub use show_event::ShowEvent;
+pub use sync_event::SyncEvent;
+pub use uninstall_event::UninstallEvent;
+pub use upgrade_event::UpgradeEvent;
@@ -0,0 +1,27 @@
+use crate::handlers::events::event::Event;
+
+pub enum OwnsEvent {
+    OwnedBy {
+        path: String,
+        name: String,
+        version: String,
+    },
+    NotOwned {
+        path: String,
+    },
+}
+
+impl Event for OwnsEvent {
+    fn message(&self) -> String {
+        match self {
+            OwnsEvent::OwnedBy {
+                path,
+                name,
+                version,
+            } => format!("==> {path} is owned by {name}@{version}"),
+            OwnsEvent::NotOwned { path } => {
+                format!("==> {path} is not owned by any package")
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use crate::handlers::events::event::Event;
+
+pub enum RegistryEvent {
+    Indexing {
+        path: String,
+    },
+    Signing {
+        path: String,
+        releases: usize,
+    },
+    Signed {
+        path: String,
+    },
+
+    Scaffolding {
+        name: String,
+        version: String,
+        repo: String,
+    },
+    UsingTag {
+        tag: String,
+    },
+    NoMatchingTag {
+        version: String,
+    },
+    DetectedBuildSystem {
+        name: Option<String>,
+    },
+
+    Linting {
+        path: String,
+    },
+    LintIssue {
+        path: String,
+        message: String,
+    },
+    LintFinished {
+        recipes: usize,
+        issues: usize,
+    },
+
+    Bumping {
+        name: String,
+        from: String,
+        to: String,
+    },
+    DroppedPin {
+        field: String,
+    },
+
+    WroteRecipe {
+        path: String,
+    },
+}
+
+impl Event for RegistryEvent {
+    fn message(&self) -> String {
+        match self {
+            RegistryEvent::Indexing { path } => format!("==> Indexing registry {path}"),
+            RegistryEvent::Signing { path, releases } => {
+                format!("-> signing {path} ({releases} releases)")
+            }
+            RegistryEvent::Signed { path } => format!("==> Wrote signature {path}"),
+            RegistryEvent::Scaffolding {
+                name,
+                version,
+                repo,
+            } => format!("==> Scaffolding {name}@{version} from {repo}"),
+            RegistryEvent::UsingTag { tag } => format!("-> using tag {tag}"),
+            RegistryEvent::NoMatchingTag { version } => {
+                format!("-> no tag found for {version}, using the default branch")
+            }
+            RegistryEvent::DetectedBuildSystem { name: Some(name) } => {
+                format!("-> detected {name} build")
+            }
+            RegistryEvent::DetectedBuildSystem { name: None } => {
+                "-> no build system detected, build steps need to be filled in".to_string()
+            }
+            RegistryEvent::Linting { path } => format!("==> Linting registry {path}"),
+            RegistryEvent::LintIssue { path, message } => format!("-> {path}: {message}"),
+            RegistryEvent::LintFinished { recipes, issues: 0 } => {
+                format!("==> {recipes} recipes, no problems found")
+            }
+            RegistryEvent::LintFinished { recipes, issues } => {
+                format!("==> {recipes} recipes, {issues} problems found")
+            }
+            RegistryEvent::Bumping { name, from, to } => {
+                format!("==> Bumping {name} {from} -> {to}")
+            }
+            RegistryEvent::DroppedPin { field } => {
+                format!("-> removed {field}, it pinned the previous release")
+            }
+            RegistryEvent::WroteRecipe { path } => format!("==> Wrote {path}"),
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use crate::handlers::events::{InstallEvent, event::Event};
+
+pub enum RollbackEvent {
+    RollingBack {
+        name: String,
+        from: String,
+        to: String,
+    },
+    RestoringFiles,
+    Install(InstallEvent),
+    Finished {
+        name: String,
+        version: String,
+    },
+}
+
+impl Event for RollbackEvent {
+    fn message(&self) -> String {
+        match self {
+            RollbackEvent::RollingBack { name, from, to } => {
+                format!("==> Rolling back {name} {from} -> {to}")
+            }
+            RollbackEvent::RestoringFiles => "==> Restoring saved build".to_string(),
+            RollbackEvent::Install(event) => event.message(),
+            RollbackEvent::Finished { name, version } => {
+                format!("==> Rolled back {name} to {version}")
+            }
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use crate::handlers::{events::event::Event, search_handler::SearchResult};
+
+pub enum SearchEvent {
+    Searching { query: String },
+    Found(SearchResult),
+    NoResults { query: String },
+}
+
+impl Event for SearchEvent {
+    fn message(&self) -> String {
+        match self {
+            SearchEvent::Searching { query } => format!("==> Searching for {query}"),
+            SearchEvent::Found(result) => {
+                let mut line = format!("-> {}@{}", result.name, result.version);
+                match &result.installed {
+                    Some(version) if *version == result.version => line.push_str(" [installed]"),
+                    Some(version) => line.push_str(&format!(" [installed {version}]")),
+                    None => {}
+                }
+                if let Some(description) = &result.description {
+                    line.push_str(&format!("\n   {description}"));
+                }
+                line
+            }
+            SearchEvent::NoResults { query } => format!("==> No packages match {query}"),
+        }
+    }
+}
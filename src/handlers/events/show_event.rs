@@ -1,20 +1,63 @@
 use crate::handlers::events::event::Event;
 
 pub enum ShowEvent {
-    LookingUpPackage { name: String },
-    PackageNotFound { name: String },
+    LookingUpPackage {
+        name: String,
+    },
+    PackageNotFound {
+        name: String,
+    },
 
-    ShowingPackage { name: String, latest: String },
-    AvailableVersions { versions: Vec<String> },
+    ShowingPackage {
+        name: String,
+        latest: String,
+    },
+    AvailableVersions {
+        versions: Vec<String>,
+    },
+    Origin {
+        registry: String,
+        shadowed: Vec<String>,
+    },
 
-    InstalledVersion { version: String },
-    InstalledPath { path: String },
+    Description {
+        text: String,
+    },
+    License {
+        license: String,
+    },
+    Homepage {
+        url: String,
+    },
+    Maintainers {
+        maintainers: Vec<String>,
+    },
+    Keywords {
+        keywords: Vec<String>,
+    },
+    Categories {
+        categories: Vec<String>,
+    },
+    Provides {
+        names: Vec<String>,
+    },
+
+    InstalledVersion {
+        version: String,
+    },
+    InstalledPath {
+        path: String,
+    },
 
     StatusUpToDate,
-    StatusOutdated { latest: String },
+    StatusOutdated {
+        latest: String,
+    },
     StatusNotInstalled,
 
-    BuildFile { path: String },
+    BuildFile {
+        path: String,
+    },
 
     Finished,
 }
@@ -34,6 +77,26 @@ impl Event for ShowEvent {
             ShowEvent::AvailableVersions { versions } => {
                 format!("-> Available versions: {versions:?}")
             }
+            ShowEvent::Origin { registry, shadowed } if shadowed.is_empty() => {
+                format!("-> Registry: {registry}")
+            }
+            ShowEvent::Origin { registry, shadowed } => {
+                format!(
+                    "-> Registry: {registry} (overrides {})",
+                    shadowed.join(", ")
+                )
+            }
+            ShowEvent::Description { text } => format!("-> Description: {text}"),
+            ShowEvent::License { license } => format!("-> License: {license}"),
+            ShowEvent::Homepage { url } => format!("-> Homepage: {url}"),
+            ShowEvent::Maintainers { maintainers } => {
+                format!("-> Maintainers: {}", maintainers.join(", "))
+            }
+            ShowEvent::Keywords { keywords } => format!("-> Keywords: {}", keywords.join(", ")),
+            ShowEvent::Categories { categories } => {
+                format!("-> Categories: {}", categories.join(", "))
+            }
+            ShowEvent::Provides { names } => format!("-> Provides: {}", names.join(", ")),
             ShowEvent::InstalledVersion { version } => {
                 format!("-> Installed version: {version}")
             }
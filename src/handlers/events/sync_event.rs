@@ -0,0 +1,49 @@
+use crate::handlers::events::event::Event;
+
+pub enum SyncEvent {
+    SyncingRemote {
+        name: String,
+        url: String,
+    },
+    RemoteFailed {
+        name: String,
+        error: String,
+    },
+    UnsignedRemote {
+        name: String,
+    },
+    Indexing,
+    PinNotSatisfied {
+        package: String,
+        pinned: String,
+        used: String,
+    },
+    Finished {
+        packages: usize,
+    },
+}
+
+impl Event for SyncEvent {
+    fn message(&self) -> String {
+        match self {
+            SyncEvent::SyncingRemote { name, url } => format!("==> Syncing {name} from {url}"),
+            SyncEvent::RemoteFailed { name, error } => {
+                format!("-> failed to sync {name}, keeping previous copy: {error}")
+            }
+            SyncEvent::UnsignedRemote { name } => {
+                format!("-> warning: {name} allows unsigned content, its recipes are not verified")
+            }
+            SyncEvent::Indexing => "==> Rebuilding registry index".to_string(),
+            SyncEvent::PinNotSatisfied {
+                package,
+                pinned,
+                used,
+            } => format!(
+                "-> {package} is pinned to {pinned}, which does not provide it; using {used}"
+            ),
+            SyncEvent::Finished { packages } => {
+                format!("==> Registry synced ({packages} packages)")
+            }
+        }
+    }
+}
@@ -5,6 +5,7 @@ pub enum UninstallEvent {
     UninstallingDependency { name: String },
     DependencyAlreadyUninstalled { name: String },
     RemovingPackageFiles,
+    RemovingFile { path: String },
     Cleanup,
     Finished,
 }
@@ -20,6 +21,7 @@ impl Event for UninstallEvent {
                 format!("-> dependency {name} already uninstalled")
             }
             UninstallEvent::RemovingPackageFiles => "==> Removing package files".to_string(),
+            UninstallEvent::RemovingFile { path } => format!("-> removing {path}"),
             UninstallEvent::Cleanup => "==> Cleanup".to_string(),
             UninstallEvent::Finished => "==> Finished".to_string(),
         }
@@ -0,0 +1,25 @@
+use crate::handlers::events::{InstallEvent, event::Event};
+
+pub enum UpgradeEvent {
+    Upgrading {
+        name: String,
+        from: String,
+        to: String,
+    },
+    Install(InstallEvent),
+    Finished {
+        name: String,
+    },
+}
+
+impl Event for UpgradeEvent {
+    fn message(&self) -> String {
+        match self {
+            UpgradeEvent::Upgrading { name, from, to } => {
+                format!("==> Upgrading {name} {from} -> {to}")
+            }
+            UpgradeEvent::Install(event) => event.message(),
+            UpgradeEvent::Finished { name } => format!("==> Upgraded {name}"),
+        }
+    }
+}
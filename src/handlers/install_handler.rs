@@ -1,19 +1,43 @@
 use crate::{
     build::{
-        build_step_handler::build_handler::BuildHandler,
+        build_step_handler::{
+            build_handler::BuildHandler,
+            build_log::{BuildLogs, LOG_LIMIT},
+            environment::BuildEnvironment,
+            sandbox::{Sandbox, SandboxTool},
+        },
         dependency_handler::{
-            build_dependency_guard::BuildDependencyGuard, dependency_handler::DependencyHandler,
+            build_dependency_guard::BuildDependencyGuard,
+            dependency_handler::DependencyHandler,
+            reponere_resolver::{
+                ReponereResolver, ResolveError, ResolvedPackage, is_reponere_dependency,
+            },
         },
+        lockfile::lockfile::{LockedPackage, LockedSource, Lockfile, recipe_sha256},
         package::{
-            package::{InstalledPackage, Package},
+            package::{
+                Dependencies, Dependency, DependencySource, InstalledBuild, InstalledPackage,
+                Manifest, Package, Source,
+            },
             parse::PackageParser,
         },
-        package_manager::manager::PackageManager,
+        package_manager::manager::{PackageManager, PackageManagerApi, PackageManagerError},
         package_tracker::package_tracker::PackageTracker,
+        patch::patch_handler::PatchHandler,
         registry::registry_handler::{Registry, Release},
-        source::source_handler::{GitSource, GitSourceHandler},
+        source::{
+            archive_handler::{ArchiveSource, ArchiveSourceHandler},
+            local_handler::{LocalSource, LocalSourceHandler},
+            source_handler::{GitSource, GitSourceHandler},
+        },
+        staging::staging_handler::{StagingHandler, remove_manifest_files},
     },
     handlers::events::InstallEvent,
+    util::config::Config,
+};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
 };
 use tempfile::TempDir;
 use thiserror::Error;
@@ -23,6 +47,31 @@ pub enum InstallResult {
     AlreadyInstalled,
 }
 
+#[derive(Debug, Default, Clone, Copy)]
+pub struct InstallOptions<'a> {
+    pub force: bool,
+    pub overwrite: bool,
+    pub lockfile: Option<&'a Lockfile>,
+    /// Releases already picked for the requested packages and their
+    /// dependencies, used instead of resolving again.
+    pub solution: Option<&'a [ResolvedPackage]>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileConflict {
+    pub path: String,
+    pub owner: Option<String>,
+}
+
+impl fmt::Display for FileConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.owner {
+            Some(owner) => write!(f, "{} (owned by {owner})", self.path),
+            None => write!(f, "{} (not owned by any package)", self.path),
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum InstallError {
     #[error("release not found: {0}")]
@@ -31,84 +80,403 @@ pub enum InstallError {
     ParseError(String),
     #[error("package manager error: {0}")]
     PackageManagerError(String),
+    #[error("dependency resolution error: {0}")]
+    DependencyResolutionError(String),
     #[error("Runtime dependency errors: {0}")]
     RuntimeDependencyError(String),
     #[error("Buildtime dependency errors: {0}")]
     BuildtimeDependencyError(String),
     #[error("source error: {0}")]
     SourceFetchError(String),
+    #[error("patch error: {0}")]
+    PatchError(String),
     #[error("build error: {0}")]
     BuildError(String),
+    #[error(
+        "sandboxed builds are enabled, but neither bwrap nor unshare can create namespaces; \
+         set `sandbox = false` to build without one"
+    )]
+    SandboxUnavailable,
+    #[error("install error: {0}")]
+    StagingError(String),
+    #[error("file conflicts:\n{}", format_conflicts(.0))]
+    FileConflicts(Vec<FileConflict>),
+    #[error("{0} is not in the lockfile")]
+    NotInLockfile(String),
+    #[error("lockfile mismatch: {0}")]
+    LockMismatch(String),
+}
+
+fn format_conflicts(conflicts: &[FileConflict]) -> String {
+    conflicts
+        .iter()
+        .map(|c| format!(" - {c}"))
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 pub fn run<F: FnMut(InstallEvent)>(
+    config: &Config,
     reg: &Registry,
     tracker: &mut PackageTracker,
     package: &str,
-    force: bool,
+    options: InstallOptions,
     progress: &mut F,
 ) -> Result<InstallResult, InstallError> {
-    if !force && check_already_installed(package, tracker) {
+    run_confirming(
+        config,
+        reg,
+        tracker,
+        package,
+        options,
+        &mut |_| false,
+        progress,
+    )
+}
+
+/// Like `run`, but asks `confirm_overwrite` before failing on file
+/// conflicts, so the staged build is installed without rebuilding.
+pub fn run_confirming<F, C>(
+    config: &Config,
+    reg: &Registry,
+    tracker: &mut PackageTracker,
+    package: &str,
+    options: InstallOptions,
+    confirm_overwrite: &mut C,
+    progress: &mut F,
+) -> Result<InstallResult, InstallError>
+where
+    F: FnMut(InstallEvent),
+    C: FnMut(&[FileConflict]) -> bool,
+{
+    let name = parse_request(package).name;
+    let locked = match options.lockfile {
+        Some(lockfile) => Some(
+            lockfile
+                .get_package(&name)
+                .ok_or_else(|| InstallError::NotInLockfile(name.clone()))?,
+        ),
+        None => None,
+    };
+
+    let pinned = match locked {
+        Some(locked) => Some(locked.version.as_str()),
+        None => options
+            .solution
+            .and_then(|solution| solution.iter().find(|p| p.name == name))
+            .map(|p| p.version.as_str()),
+    };
+
+    let already_installed = tracker
+        .get_package(&name)
+        .is_some_and(|installed| pinned.is_none_or(|version| version == installed.version));
+    if !options.force && already_installed {
         return Ok(InstallResult::AlreadyInstalled);
     }
-    let release = resolve_release(package, reg)?;
-    let parsed = parse_package(release)?;
 
-    progress(InstallEvent::InstallingDependencies);
-    let installed = install_dependencies(&parsed, progress)?;
+    let release = match pinned {
+        Some(version) => reg
+            .resolve_release(&name, Some(version))
+            .ok_or_else(|| InstallError::ReleaseNotFound(name.clone()))?,
+        None => resolve_release(package, reg, tracker)?,
+    };
+    let mut parsed = parse_package(release)?;
+    let package_manager = PackageManager::get_package_manager(true)
+        .map_err(|e| InstallError::PackageManagerError(e.to_string()))?;
+    if let Some(locked) = locked {
+        verify_locked_recipe(locked, release, &parsed)?;
+        verify_locked_system_dependencies(locked, &package_manager)?;
+        parsed.source = locked.source.pin(&parsed.source);
+    }
 
+    progress(InstallEvent::InstallingDependencies);
+    install_reponere_dependencies(
+        config,
+        reg,
+        tracker,
+        &parsed,
+        options,
+        confirm_overwrite,
+        progress,
+    )?;
+    let installed = install_dependencies(&system_dependencies(&parsed, reg), progress)?;
     let _build_dependency_guard = BuildDependencyGuard {
-        package_manager: &PackageManager::get_package_manager(true)
-            .map_err(|e| InstallError::PackageManagerError(e.to_string()))?,
+        package_manager: &package_manager,
         installed,
     };
 
     progress(InstallEvent::FetchingSource);
-    let source_dir = fetch_source(&parsed)?;
+    let source_dir = fetch_source(config, &parsed, release)?;
+
+    if !parsed.patches.is_empty() {
+        progress(InstallEvent::ApplyingPatches);
+        apply_patches(&parsed, release, &source_dir, progress)?;
+    }
+
+    let staging =
+        StagingHandler::new("/").map_err(|e| InstallError::StagingError(e.to_string()))?;
 
     progress(InstallEvent::BuildingSource);
-    optional_build(&parsed, &source_dir, progress)?;
+    optional_build(config, &parsed, &source_dir, staging.path(), progress)?;
+
+    if !options.overwrite {
+        check_conflicts(&parsed, &staging, tracker, confirm_overwrite)?;
+    }
+
+    progress(InstallEvent::SavingBuild);
+    let archive = archive_build(config, &parsed, &staging)?;
+
+    progress(InstallEvent::InstallingFiles);
+    let manifest = staging
+        .install(progress)
+        .map_err(|e| InstallError::StagingError(e.to_string()))?;
+    remove_stale_files(&parsed, &manifest, tracker, progress)?;
 
     progress(InstallEvent::Cleanup);
-    track_installation(&parsed, tracker)?;
+    track_installation(&parsed, reg, manifest, tracker)?;
+    record_build(
+        config,
+        &parsed,
+        reg,
+        archive.path.to_string_lossy().to_string(),
+        tracker,
+    );
+    archive
+        .save()
+        .map_err(|e| InstallError::StagingError(e.to_string()))?;
     drop(_build_dependency_guard);
 
     progress(InstallEvent::Finished);
     Ok(InstallResult::Installed)
 }
 
-fn check_already_installed(package: &str, tracker: &PackageTracker) -> bool {
-    tracker.get_package(package).is_some()
+/// Checks that all requested packages can be installed together before
+/// anything is built. Installed packages in `removing` are ignored.
+pub fn resolve_requests(
+    reg: &Registry,
+    tracker: &PackageTracker,
+    packages: &[String],
+    removing: &[String],
+) -> Result<Vec<ResolvedPackage>, InstallError> {
+    let requests: Vec<Dependency> = packages.iter().map(|p| parse_request(p)).collect();
+
+    ReponereResolver::new(reg)
+        .with_installed(tracker)
+        .with_removing(removing)
+        .solve(&requests)
+        .map_err(|e| match e {
+            ResolveError::NotInRegistry(name) => InstallError::ReleaseNotFound(name),
+            e => InstallError::DependencyResolutionError(e.to_string()),
+        })
 }
 
-fn resolve_release<'a>(package: &str, reg: &'a Registry) -> Result<&'a Release, InstallError> {
-    if let Some(package) = package.split_once('@') {
-        reg.resolve_release(package.0, Some(package.1))
-            .ok_or(InstallError::ReleaseNotFound(package.0.to_string()))
-    } else {
-        reg.resolve_release(package, None)
-            .ok_or(InstallError::ReleaseNotFound(package.to_string()))
+fn parse_request(package: &str) -> Dependency {
+    let (name, version_req) = match package.split_once('@') {
+        Some((name, version_req)) => (name, Some(version_req.to_string())),
+        None => (package, None),
+    };
+
+    Dependency {
+        name: name.to_string(),
+        version_req,
+        source: Some(DependencySource::Reponere),
     }
 }
 
+fn resolve_release<'a>(
+    package: &str,
+    reg: &'a Registry,
+    tracker: &PackageTracker,
+) -> Result<&'a Release, InstallError> {
+    let name = parse_request(package).name;
+
+    resolve_requests(reg, tracker, &[package.to_string()], &[])?
+        .iter()
+        .find(|p| p.name == name)
+        .and_then(|p| reg.resolve_release(&p.name, Some(&p.version)))
+        .ok_or(InstallError::ReleaseNotFound(name))
+}
+
 fn parse_package(release: &Release) -> Result<Package, InstallError> {
     PackageParser::new(release.build_file())
         .parse()
         .map_err(|e| InstallError::ParseError(e.to_string()))
 }
 
-fn install_dependencies<F: FnMut(InstallEvent)>(
+fn install_reponere_dependencies<F, C>(
+    config: &Config,
+    reg: &Registry,
+    tracker: &mut PackageTracker,
+    parsed: &Package,
+    options: InstallOptions,
+    confirm_overwrite: &mut C,
+    progress: &mut F,
+) -> Result<(), InstallError>
+where
+    F: FnMut(InstallEvent),
+    C: FnMut(&[FileConflict]) -> bool,
+{
+    let order = match (options.lockfile, options.solution) {
+        (None, None) => ReponereResolver::new(reg)
+            .with_installed(tracker)
+            .resolve(parsed)
+            .map_err(|e| InstallError::DependencyResolutionError(e.to_string()))?,
+        // each pinned dependency installs its own dependencies first, which
+        // keeps the topological order without resolving again
+        _ => parsed
+            .dependencies
+            .runtime
+            .iter()
+            .chain(&parsed.dependencies.build)
+            .filter(|d| is_reponere_dependency(d, reg))
+            .map(|d| pinned_release(options, &d.name))
+            .collect::<Result<Vec<_>, _>>()?,
+    };
+
+    for dependency in order {
+        let satisfied = tracker
+            .get_package(&dependency.name)
+            .is_some_and(|installed| installed.version == dependency.version);
+        if satisfied {
+            progress(InstallEvent::DependencyAlreadyInstalled {
+                name: dependency.name,
+            });
+            continue;
+        }
+
+        progress(InstallEvent::InstallingReponereDependency {
+            name: dependency.name.clone(),
+            version: dependency.version.clone(),
+        });
+        run_confirming(
+            config,
+            reg,
+            tracker,
+            &format!("{}@{}", dependency.name, dependency.version),
+            InstallOptions {
+                force: true,
+                ..options
+            },
+            confirm_overwrite,
+            progress,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// The release of `name` picked by the lockfile or the solution passed in.
+fn pinned_release(options: InstallOptions, name: &str) -> Result<ResolvedPackage, InstallError> {
+    match options.lockfile {
+        Some(lockfile) => lockfile
+            .get_package(name)
+            .map(|locked| ResolvedPackage {
+                name: locked.name.clone(),
+                version: locked.version.clone(),
+            })
+            .ok_or_else(|| InstallError::NotInLockfile(name.to_string())),
+        None => options
+            .solution
+            .and_then(|solution| solution.iter().find(|p| p.name == name))
+            .cloned()
+            .ok_or_else(|| {
+                InstallError::DependencyResolutionError(format!(
+                    "{name} is missing from the resolved packages"
+                ))
+            }),
+    }
+}
+
+fn verify_locked_recipe(
+    locked: &LockedPackage,
+    release: &Release,
     parsed: &Package,
+) -> Result<(), InstallError> {
+    let sha256 = recipe_sha256(Path::new(release.build_file()))
+        .map_err(|e| InstallError::ParseError(e.to_string()))?;
+
+    if sha256 != locked.recipe_sha256 || !locked.source.matches(&parsed.source) {
+        return Err(InstallError::LockMismatch(format!(
+            "recipe for {}@{} changed since it was locked",
+            locked.name, locked.version
+        )));
+    }
+
+    if let LockedSource::Local { blake3, .. } = &locked.source {
+        let source = LocalSource::from_source(&parsed.source)
+            .map_err(|e| InstallError::SourceFetchError(e.to_string()))?;
+        let digest = LocalSourceHandler::new(source, recipe_dir(release))
+            .blake3()
+            .map_err(|e| InstallError::SourceFetchError(e.to_string()))?;
+        if digest != *blake3 {
+            return Err(InstallError::LockMismatch(format!(
+                "local source of {}@{} changed since it was locked",
+                locked.name, locked.version
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Checks the system dependencies against the lockfile before anything is
+/// installed, so build dependencies that get removed afterwards still match.
+pub fn verify_locked_system_dependencies<PM: PackageManagerApi>(
+    locked: &LockedPackage,
+    package_manager: &PM,
+) -> Result<(), InstallError> {
+    for dependency in &locked.system_dependencies {
+        let version = system_dependency_version(package_manager, &dependency.name)
+            .map_err(|e| InstallError::PackageManagerError(e.to_string()))?;
+
+        if version != dependency.version {
+            return Err(InstallError::LockMismatch(format!(
+                "{} is {}, locked at {}",
+                dependency.name,
+                version.as_deref().unwrap_or("unavailable"),
+                dependency.version.as_deref().unwrap_or("unavailable"),
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// The version a system dependency resolves to: the installed one, or the one
+/// the package manager would install.
+pub(crate) fn system_dependency_version<PM: PackageManagerApi>(
+    package_manager: &PM,
+    name: &str,
+) -> Result<Option<String>, PackageManagerError> {
+    match package_manager.get_installed_version(name)? {
+        Some(version) => Ok(Some(version)),
+        None => package_manager.get_available_version(name),
+    }
+}
+
+fn system_dependencies(parsed: &Package, reg: &Registry) -> Dependencies {
+    let system = |deps: &[Dependency]| {
+        deps.iter()
+            .filter(|d| !is_reponere_dependency(d, reg))
+            .cloned()
+            .collect()
+    };
+
+    Dependencies {
+        runtime: system(&parsed.dependencies.runtime),
+        build: system(&parsed.dependencies.build),
+    }
+}
+
+fn install_dependencies<F: FnMut(InstallEvent)>(
+    dependencies: &Dependencies,
     progress: &mut F,
 ) -> Result<Vec<String>, InstallError> {
     let package_manager = PackageManager::get_package_manager(true)
         .map_err(|e| InstallError::PackageManagerError(e.to_string()))?;
 
-    let dependency_handler = DependencyHandler::new(&package_manager, parsed.dependencies.clone());
+    let dependency_handler = DependencyHandler::new(&package_manager, dependencies.clone());
 
     progress(InstallEvent::InstallingRunTimeDependencies {
-        dependencies: parsed
-            .dependencies
+        dependencies: dependencies
             .runtime
             .iter()
             .map(|d| d.name.clone())
@@ -129,12 +497,7 @@ fn install_dependencies<F: FnMut(InstallEvent)>(
     }
 
     progress(InstallEvent::InstallingBuildDependencies {
-        dependencies: parsed
-            .dependencies
-            .build
-            .iter()
-            .map(|d| d.name.clone())
-            .collect(),
+        dependencies: dependencies.build.iter().map(|d| d.name.clone()).collect(),
     });
     let mut build_errors = Vec::new();
     let installed = dependency_handler.install_build_dependencies(&mut build_errors, progress);
@@ -151,37 +514,237 @@ fn install_dependencies<F: FnMut(InstallEvent)>(
     Ok(installed)
 }
 
-fn fetch_source(parsed: &Package) -> Result<TempDir, InstallError> {
-    let source = GitSource::from_source(&parsed.source)
-        .map_err(|e| InstallError::SourceFetchError(e.to_string()))?;
+fn fetch_source(
+    config: &Config,
+    parsed: &Package,
+    release: &Release,
+) -> Result<TempDir, InstallError> {
+    match &parsed.source {
+        Source::Git { .. } => {
+            let source = GitSource::from_source(&parsed.source)
+                .map_err(|e| InstallError::SourceFetchError(e.to_string()))?;
+
+            GitSourceHandler::new(source)
+                .with_cache(&config.cache_path)
+                .fetch()
+                .map_err(|e| InstallError::SourceFetchError(e.to_string()))
+        }
+        Source::Archive { .. } => {
+            let source = ArchiveSource::from_source(&parsed.source)
+                .map_err(|e| InstallError::SourceFetchError(e.to_string()))?;
+
+            ArchiveSourceHandler::new(source)
+                .fetch()
+                .map_err(|e| InstallError::SourceFetchError(e.to_string()))
+        }
+        Source::Local { .. } => {
+            let source = LocalSource::from_source(&parsed.source)
+                .map_err(|e| InstallError::SourceFetchError(e.to_string()))?;
+            LocalSourceHandler::new(source, recipe_dir(release))
+                .fetch()
+                .map_err(|e| InstallError::SourceFetchError(e.to_string()))
+        }
+    }
+}
 
-    GitSourceHandler::new(source)
-        .fetch()
-        .map_err(|e| InstallError::SourceFetchError(e.to_string()))
+fn apply_patches<F: FnMut(InstallEvent)>(
+    parsed: &Package,
+    release: &Release,
+    source_dir: &TempDir,
+    progress: &mut F,
+) -> Result<(), InstallError> {
+    PatchHandler::new(parsed.patches.clone(), recipe_dir(release))
+        .apply_patches(source_dir.path(), progress)
+        .map_err(|e| InstallError::PatchError(e.to_string()))
 }
 
 fn optional_build<F: FnMut(InstallEvent)>(
+    config: &Config,
     parsed: &Package,
     source_dir: &TempDir,
+    destdir: &Path,
     progress: &mut F,
 ) -> Result<(), InstallError> {
     if let Some(build) = &parsed.build {
-        BuildHandler::new(build.clone())
-            .run_build_steps(source_dir.path(), progress)
-            .map_err(|e| InstallError::BuildError(e.to_string()))?;
+        let name = parsed.name.trim();
+        let environment = BuildEnvironment::new(&config.env_passthrough)
+            .with_overrides(&config.build_env)
+            .with_var("PREFIX", config.prefix.to_string_lossy())
+            .with_var("JOBS", config.jobs().to_string())
+            .with_var("PKG_NAME", name)
+            .with_var("PKG_VERSION", parsed.version.as_str());
+
+        let logs = BuildLogs::new(&config.log_path);
+        let mut handler = BuildHandler::new(build.clone())
+            .with_destdir(destdir)
+            .with_environment(environment)
+            .with_checks(config.checks)
+            .with_log(logs.next_path(name, &parsed.version));
+        if config.sandbox {
+            let tool = SandboxTool::detect().ok_or(InstallError::SandboxUnavailable)?;
+            handler = handler.with_sandbox(Sandbox::new(tool).with_network(build.network));
+        }
+
+        let result = handler.run_build_steps(source_dir.path(), progress);
+        let _ = logs.prune(name, LOG_LIMIT);
+        result.map_err(|e| InstallError::BuildError(e.to_string()))?;
     }
 
     Ok(())
 }
 
-fn track_installation(parsed: &Package, tracker: &mut PackageTracker) -> Result<(), InstallError> {
+pub(crate) fn recipe_dir(release: &Release) -> &Path {
+    Path::new(release.build_file())
+        .parent()
+        .unwrap_or(Path::new("."))
+}
+
+pub fn find_conflicts(
+    package: &str,
+    files: &[String],
+    tracker: &PackageTracker,
+) -> Vec<FileConflict> {
+    files
+        .iter()
+        .filter_map(|path| match tracker.owner_of(path) {
+            Some(owner) if owner.name == package => None,
+            Some(owner) => Some(FileConflict {
+                path: path.clone(),
+                owner: Some(owner.name.clone()),
+            }),
+            None if Path::new(path).symlink_metadata().is_ok() => Some(FileConflict {
+                path: path.clone(),
+                owner: None,
+            }),
+            None => None,
+        })
+        .collect()
+}
+
+fn check_conflicts<C: FnMut(&[FileConflict]) -> bool>(
+    parsed: &Package,
+    staging: &StagingHandler,
+    tracker: &PackageTracker,
+    confirm_overwrite: &mut C,
+) -> Result<(), InstallError> {
+    let files = staging
+        .staged_files()
+        .map_err(|e| InstallError::StagingError(e.to_string()))?;
+
+    let conflicts = find_conflicts(&parsed.name, &files, tracker);
+    if conflicts.is_empty() || confirm_overwrite(&conflicts) {
+        Ok(())
+    } else {
+        Err(InstallError::FileConflicts(conflicts))
+    }
+}
+
+fn remove_stale_files<F: FnMut(InstallEvent)>(
+    parsed: &Package,
+    manifest: &Manifest,
+    tracker: &PackageTracker,
+    progress: &mut F,
+) -> Result<(), InstallError> {
+    let Some(previous) = tracker.get_package(&parsed.name) else {
+        return Ok(());
+    };
+
+    let stale = previous.manifest.difference(manifest);
+    if stale.is_empty() {
+        return Ok(());
+    }
+
+    progress(InstallEvent::RemovingStaleFiles);
+    remove_manifest_files(&stale).map_err(|e| InstallError::StagingError(e.to_string()))
+}
+
+fn track_installation(
+    parsed: &Package,
+    reg: &Registry,
+    manifest: Manifest,
+    tracker: &mut PackageTracker,
+) -> Result<(), InstallError> {
     let installed = InstalledPackage {
         name: parsed.name.clone(),
         version: parsed.version.clone(),
-        install_path: parsed.install_path.clone().unwrap(),
-        dependencies: parsed.dependencies.runtime.clone(),
+        install_path: parsed.install_path.clone().unwrap_or_default(),
+        dependencies: resolved_dependencies(parsed, reg),
+        manifest,
     };
+    tracker.take_ownership(&installed.name, &installed.manifest.files);
 
     tracker.add_package(installed);
     Ok(())
 }
+
+/// A history archive written under a temporary name. It only takes its
+/// real name once the build is installed and recorded.
+struct PendingArchive {
+    temporary: PathBuf,
+    path: PathBuf,
+}
+
+impl PendingArchive {
+    fn save(self) -> std::io::Result<()> {
+        std::fs::rename(&self.temporary, &self.path)
+    }
+}
+
+impl Drop for PendingArchive {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.temporary);
+    }
+}
+
+fn archive_build(
+    config: &Config,
+    parsed: &Package,
+    staging: &StagingHandler,
+) -> Result<PendingArchive, InstallError> {
+    let directory = config.history_path.join(&parsed.name);
+    let archive = PendingArchive {
+        temporary: directory.join(format!(".{}.tar.gz.reponere-new", parsed.version)),
+        path: directory.join(format!("{}.tar.gz", parsed.version)),
+    };
+
+    staging
+        .archive(&archive.temporary)
+        .map_err(|e| InstallError::StagingError(e.to_string()))?;
+
+    Ok(archive)
+}
+
+fn record_build(
+    config: &Config,
+    parsed: &Package,
+    reg: &Registry,
+    archive: String,
+    tracker: &mut PackageTracker,
+) {
+    let build = InstalledBuild {
+        version: parsed.version.clone(),
+        install_path: parsed.install_path.clone().unwrap_or_default(),
+        dependencies: resolved_dependencies(parsed, reg),
+        archive,
+    };
+
+    for dropped in tracker.record_build(&parsed.name, build, config.history_limit) {
+        let _ = std::fs::remove_file(dropped.archive);
+    }
+}
+
+fn resolved_dependencies(parsed: &Package, reg: &Registry) -> Vec<Dependency> {
+    parsed
+        .dependencies
+        .runtime
+        .iter()
+        .map(|d| Dependency {
+            source: Some(if is_reponere_dependency(d, reg) {
+                DependencySource::Reponere
+            } else {
+                DependencySource::System
+            }),
+            ..d.clone()
+        })
+        .collect()
+}
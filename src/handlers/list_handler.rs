@@ -7,7 +7,6 @@ pub enum ListError {
     PackageNotFound(String),
 }
 
-
 pub fn run<F: FnMut(ListEvent)>(
     ctx: &Context,
     packages: Vec<String>,
@@ -21,6 +20,7 @@ pub fn run<F: FnMut(ListEvent)>(
                 progress(ListEvent::AvailablePackage(
                     name.clone(),
                     package.latest.clone(),
+                    package.metadata.description.clone(),
                 ));
             }
             return Ok(());
@@ -30,6 +30,7 @@ pub fn run<F: FnMut(ListEvent)>(
                 Some(package) => progress(ListEvent::AvailablePackage(
                     package_name.clone(),
                     package.latest.clone(),
+                    package.metadata.description.clone(),
                 )),
                 None => return Err(ListError::PackageNotFound(package_name)),
             }
@@ -45,6 +46,7 @@ pub fn run<F: FnMut(ListEvent)>(
             progress(ListEvent::InstalledPackage(
                 name.clone(),
                 package.version.clone(),
+                description(ctx, name),
             ));
         }
         return Ok(());
@@ -55,6 +57,7 @@ pub fn run<F: FnMut(ListEvent)>(
             Some(package) => progress(ListEvent::InstalledPackage(
                 package.name.clone(),
                 package.version.clone(),
+                description(ctx, &package.name),
             )),
             None => return Err(ListError::PackageNotFound(package)),
         }
@@ -62,3 +65,9 @@ pub fn run<F: FnMut(ListEvent)>(
 
     Ok(())
 }
+
+fn description(ctx: &Context, name: &str) -> Option<String> {
+    ctx.registry
+        .get_package(name)
+        .and_then(|entry| entry.metadata.description.clone())
+}
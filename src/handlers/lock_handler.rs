@@ -0,0 +1,157 @@
+use std::path::Path;
+
+use crate::{
+    build::{
+        dependency_handler::reponere_resolver::is_reponere_dependency,
+        lockfile::lockfile::{
+            LockedPackage, LockedSource, LockedSystemDependency, Lockfile, LockfileError,
+            recipe_sha256,
+        },
+        package::{
+            package::{Dependency, Package, Source},
+            parse::PackageParser,
+        },
+        package_manager::manager::PackageManagerApi,
+        package_tracker::package_tracker::PackageTracker,
+        registry::registry_handler::{Registry, Release},
+        source::{
+            archive_handler::{ArchiveSource, ArchiveSourceHandler},
+            local_handler::{LocalSource, LocalSourceHandler},
+            source_handler::{GitSource, GitSourceHandler},
+        },
+    },
+    handlers::{
+        events::LockEvent,
+        install_handler::{self, InstallError},
+    },
+    util::config::Config,
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum LockError {
+    #[error("{0}")]
+    InstallError(#[from] InstallError),
+    #[error("release not found: {0}")]
+    ReleaseNotFound(String),
+    #[error("parse error: {0}")]
+    ParseError(String),
+    #[error("source error: {0}")]
+    SourceError(String),
+    #[error("package manager error: {0}")]
+    PackageManagerError(String),
+    #[error("lockfile error: {0}")]
+    LockfileError(#[from] LockfileError),
+}
+
+pub fn run<PM: PackageManagerApi, F: FnMut(LockEvent)>(
+    config: &Config,
+    reg: &Registry,
+    tracker: &PackageTracker,
+    package_manager: &PM,
+    packages: &[String],
+    progress: &mut F,
+) -> Result<Lockfile, LockError> {
+    progress(LockEvent::Resolving);
+    let resolved = install_handler::resolve_requests(reg, tracker, packages, &[])?;
+
+    let mut lockfile = Lockfile::default();
+    for package in resolved {
+        progress(LockEvent::Locking {
+            name: package.name.clone(),
+            version: package.version.clone(),
+        });
+
+        let release = reg
+            .resolve_release(&package.name, Some(&package.version))
+            .ok_or_else(|| LockError::ReleaseNotFound(package.name.clone()))?;
+        let parsed = PackageParser::new(release.build_file())
+            .parse()
+            .map_err(|e| LockError::ParseError(e.to_string()))?;
+
+        lockfile.packages.push(LockedPackage {
+            name: parsed.name.clone(),
+            version: parsed.version.clone(),
+            recipe_sha256: recipe_sha256(Path::new(release.build_file()))
+                .map_err(|e| LockError::ParseError(e.to_string()))?,
+            source: lock_source(config, &parsed.source, release)?,
+            dependencies: dependencies(&parsed, reg, true)
+                .map(|d| d.name.clone())
+                .collect(),
+            system_dependencies: dependencies(&parsed, reg, false)
+                .map(|d| {
+                    install_handler::system_dependency_version(package_manager, &d.name)
+                        .map(|version| LockedSystemDependency {
+                            name: d.name.clone(),
+                            version,
+                        })
+                        .map_err(|e| LockError::PackageManagerError(e.to_string()))
+                })
+                .collect::<Result<_, _>>()?,
+        });
+    }
+
+    progress(LockEvent::Finished {
+        count: lockfile.packages.len(),
+    });
+    Ok(lockfile)
+}
+
+fn dependencies<'p>(
+    parsed: &'p Package,
+    reg: &'p Registry,
+    reponere: bool,
+) -> impl Iterator<Item = &'p Dependency> {
+    parsed
+        .dependencies
+        .runtime
+        .iter()
+        .chain(&parsed.dependencies.build)
+        .filter(move |d| is_reponere_dependency(d, reg) == reponere)
+}
+
+fn lock_source(
+    config: &Config,
+    source: &Source,
+    release: &Release,
+) -> Result<LockedSource, LockError> {
+    match source {
+        Source::Git { repo, .. } => {
+            let git = GitSource::from_source(source)
+                .map_err(|e| LockError::SourceError(e.to_string()))?;
+            let commit = GitSourceHandler::new(git)
+                .with_cache(&config.cache_path)
+                .resolve_commit()
+                .map_err(|e| LockError::SourceError(e.to_string()))?;
+
+            Ok(LockedSource::Git {
+                repo: repo.clone(),
+                commit,
+            })
+        }
+        Source::Archive { url, .. } => {
+            let archive = ArchiveSource::from_source(source)
+                .map_err(|e| LockError::SourceError(e.to_string()))?;
+            let sha256 = ArchiveSourceHandler::new(archive)
+                .sha256()
+                .map_err(|e| LockError::SourceError(e.to_string()))?;
+
+            Ok(LockedSource::Archive {
+                url: url.clone(),
+                sha256,
+            })
+        }
+        Source::Local { path, .. } => {
+            let local = LocalSource::from_source(source)
+                .map_err(|e| LockError::SourceError(e.to_string()))?;
+            let blake3 = LocalSourceHandler::new(local, install_handler::recipe_dir(release))
+                .blake3()
+                .map_err(|e| LockError::SourceError(e.to_string()))?;
+
+            Ok(LockedSource::Local {
+                path: path.clone(),
+                blake3,
+            })
+        }
+    }
+}
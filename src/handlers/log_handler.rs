@@ -0,0 +1,44 @@
+use std::{fs, path::PathBuf};
+
+use crate::{
+    build::build_step_handler::build_log::BuildLogs, handlers::events::LogEvent,
+    util::config::Config,
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum LogError {
+    #[error("no build log for {0}")]
+    NoLog(String),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Shows the most recent build log of `name`, or only its last `tail` lines.
+pub fn run<F: FnMut(LogEvent)>(
+    config: &Config,
+    name: &str,
+    tail: Option<usize>,
+    progress: &mut F,
+) -> Result<PathBuf, LogError> {
+    let path = BuildLogs::new(&config.log_path)
+        .latest(name)?
+        .ok_or_else(|| LogError::NoLog(name.to_string()))?;
+
+    progress(LogEvent::ShowingLog {
+        name: name.to_string(),
+        path: path.display().to_string(),
+    });
+
+    let contents = fs::read(&path)?;
+    let contents = String::from_utf8_lossy(&contents);
+    let lines: Vec<&str> = contents.lines().collect();
+    let skip = tail.map_or(0, |tail| lines.len().saturating_sub(tail));
+    for line in &lines[skip..] {
+        progress(LogEvent::Line {
+            line: line.to_string(),
+        });
+    }
+
+    Ok(path)
+}
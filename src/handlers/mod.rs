@@ -1,5 +1,15 @@
+pub mod apply_handler;
+pub mod cache_handler;
 pub mod events;
 pub mod install_handler;
 pub mod list_handler;
+pub mod lock_handler;
+pub mod log_handler;
+pub mod owns_handler;
+pub mod registry_handler;
+pub mod rollback_handler;
+pub mod search_handler;
 pub mod show_handler;
+pub mod sync_handler;
 pub mod uninstall_handler;
+pub mod upgrade_handler;
@@ -0,0 +1,30 @@
+use std::path::{self, Path};
+
+use crate::{build::package_tracker::package_tracker::PackageTracker, handlers::events::OwnsEvent};
+
+pub fn run<F: FnMut(OwnsEvent)>(tracker: &PackageTracker, path: &str, progress: &mut F) {
+    let path = resolve_path(path);
+
+    match tracker.owner_of(&path) {
+        Some(owner) => progress(OwnsEvent::OwnedBy {
+            path,
+            name: owner.name.clone(),
+            version: owner.version.clone(),
+        }),
+        None => progress(OwnsEvent::NotOwned { path }),
+    }
+}
+
+fn resolve_path(path: &str) -> String {
+    let expanded = match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .map(|home| home.join(rest))
+            .unwrap_or_else(|| Path::new(path).to_path_buf()),
+        None => Path::new(path).to_path_buf(),
+    };
+
+    path::absolute(&expanded)
+        .unwrap_or(expanded)
+        .to_string_lossy()
+        .to_string()
+}
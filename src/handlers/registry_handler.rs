@@ -0,0 +1,277 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    build::{
+        dependency_handler::version::{VersionError, is_newer},
+        package::parse::PackageParser,
+        registry::{
+            authoring::{self, BuildSystem, RecipeTemplate},
+            remote::{REMOTE_INDEX_FILE, RemoteError, RemoteIndex},
+            signature::{self, SignatureError},
+        },
+    },
+    handlers::events::RegistryEvent,
+};
+use git2::Repository;
+use tempfile::TempDir;
+use thiserror::Error;
+use walkdir::WalkDir;
+
+const BUILD_FILE: &str = "package_build.yaml";
+
+#[derive(Debug, Error)]
+pub enum RegistryError {
+    #[error("io error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("git error: {0}")]
+    GitError(#[from] git2::Error),
+
+    #[error("{0}")]
+    RemoteError(#[from] RemoteError),
+
+    #[error("{0}")]
+    SignatureError(#[from] SignatureError),
+
+    #[error("failed to write index: {0}")]
+    JsonError(#[from] serde_json::Error),
+
+    #[error("{0} already exists")]
+    AlreadyExists(String),
+
+    #[error("{0} has no releases in this registry")]
+    PackageNotFound(String),
+
+    #[error("{path} is not a valid recipe: {reason}")]
+    InvalidRecipe { path: String, reason: String },
+
+    #[error("{0}")]
+    InvalidVersion(#[from] VersionError),
+}
+
+/// Writes the index of the registry at `dir`, with the checksum of every
+/// release file, and signs it with the ssh private key at `key`.
+pub fn sign<F: FnMut(RegistryEvent)>(
+    dir: &Path,
+    key: &Path,
+    progress: &mut F,
+) -> Result<(), RegistryError> {
+    progress(RegistryEvent::Indexing {
+        path: dir.display().to_string(),
+    });
+
+    let index = RemoteIndex::from_directory(dir)?;
+    let releases = index.packages.values().map(|r| r.len()).sum();
+
+    let index_path = dir.join(REMOTE_INDEX_FILE);
+    fs::write(&index_path, serde_json::to_string_pretty(&index)?)?;
+
+    progress(RegistryEvent::Signing {
+        path: index_path.display().to_string(),
+        releases,
+    });
+    let signature = signature::sign(&index_path, key)?;
+
+    progress(RegistryEvent::Signed {
+        path: signature.display().to_string(),
+    });
+    Ok(())
+}
+
+/// Scaffolds `name/version/package_build.yaml` in the registry at `dir` from
+/// the git repository at `repo`, picking the tag for `version` and the build
+/// helper for the detected build system.
+pub fn new<F: FnMut(RegistryEvent)>(
+    dir: &Path,
+    name: &str,
+    version: &str,
+    repo: &str,
+    progress: &mut F,
+) -> Result<PathBuf, RegistryError> {
+    let recipe = dir.join(name).join(version).join(BUILD_FILE);
+    if recipe.exists() {
+        return Err(RegistryError::AlreadyExists(recipe.display().to_string()));
+    }
+
+    progress(RegistryEvent::Scaffolding {
+        name: name.to_string(),
+        version: version.to_string(),
+        repo: repo.to_string(),
+    });
+
+    let checkout = TempDir::new()?;
+    let repository = Repository::clone(repo, checkout.path())?;
+
+    let tags: Vec<String> = repository
+        .tag_names(None)?
+        .iter()
+        .flatten()
+        .map(str::to_string)
+        .collect();
+    let tag = authoring::find_version_tag(&tags, name, version);
+
+    let tree = match &tag {
+        Some(tag) => {
+            progress(RegistryEvent::UsingTag { tag: tag.clone() });
+            repository
+                .revparse_single(&format!("refs/tags/{tag}"))?
+                .peel_to_tree()?
+        }
+        None => {
+            progress(RegistryEvent::NoMatchingTag {
+                version: version.to_string(),
+            });
+            repository.head()?.peel_to_tree()?
+        }
+    };
+
+    let files: Vec<String> = tree
+        .iter()
+        .filter_map(|entry| entry.name().map(str::to_string))
+        .collect();
+    let build_system = BuildSystem::detect(&files);
+    progress(RegistryEvent::DetectedBuildSystem {
+        name: build_system.map(|b| b.name().to_string()),
+    });
+
+    let template = RecipeTemplate {
+        name,
+        version,
+        repo,
+        tag: tag.as_deref(),
+        build_system,
+    };
+    fs::create_dir_all(recipe.parent().unwrap())?;
+    fs::write(&recipe, template.render())?;
+
+    progress(RegistryEvent::WroteRecipe {
+        path: recipe.display().to_string(),
+    });
+    Ok(recipe)
+}
+
+/// Parses and checks every recipe in the registry at `dir`, returning the
+/// number of problems found.
+pub fn lint<F: FnMut(RegistryEvent)>(dir: &Path, progress: &mut F) -> Result<usize, RegistryError> {
+    progress(RegistryEvent::Linting {
+        path: dir.display().to_string(),
+    });
+
+    let mut recipes = 0;
+    let mut issues = 0;
+    for entry in WalkDir::new(dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'))
+    {
+        let entry = entry.map_err(std::io::Error::from)?;
+        if entry.depth() != 3 || entry.file_name() != BUILD_FILE {
+            continue;
+        }
+        recipes += 1;
+
+        let path = entry.path();
+        let version_dir = path.parent().unwrap();
+        let version = version_dir.file_name().unwrap().to_string_lossy();
+        let name = version_dir
+            .parent()
+            .unwrap()
+            .file_name()
+            .unwrap()
+            .to_string_lossy();
+
+        let problems = match PackageParser::new(path.to_string_lossy()).parse() {
+            Ok(package) => authoring::lint_package(&package, &name, &version),
+            Err(e) => vec![format!("failed to parse: {e}")],
+        };
+
+        for message in problems {
+            issues += 1;
+            progress(RegistryEvent::LintIssue {
+                path: path.display().to_string(),
+                message,
+            });
+        }
+    }
+
+    progress(RegistryEvent::LintFinished { recipes, issues });
+    Ok(issues)
+}
+
+/// Copies the latest release of `name` forward to `to`, updating its
+/// version and tag.
+pub fn bump<F: FnMut(RegistryEvent)>(
+    dir: &Path,
+    name: &str,
+    to: &str,
+    progress: &mut F,
+) -> Result<PathBuf, RegistryError> {
+    let package_dir = dir.join(name);
+    let target_dir = package_dir.join(to);
+    if target_dir.exists() {
+        return Err(RegistryError::AlreadyExists(
+            target_dir.display().to_string(),
+        ));
+    }
+
+    let mut latest: Option<String> = None;
+    if package_dir.is_dir() {
+        for entry in fs::read_dir(&package_dir)? {
+            let entry = entry?;
+            let version = entry.file_name().to_string_lossy().to_string();
+            if !entry.path().join(BUILD_FILE).is_file() {
+                continue;
+            }
+            let newer = match &latest {
+                Some(latest) => is_newer(&version, latest)?,
+                None => true,
+            };
+            if newer {
+                latest = Some(version);
+            }
+        }
+    }
+    let from = latest.ok_or_else(|| RegistryError::PackageNotFound(name.to_string()))?;
+    let source_dir = package_dir.join(&from);
+
+    progress(RegistryEvent::Bumping {
+        name: name.to_string(),
+        from: from.clone(),
+        to: to.to_string(),
+    });
+
+    // patches and other release files come along
+    for entry in WalkDir::new(&source_dir).min_depth(1) {
+        let entry = entry.map_err(std::io::Error::from)?;
+        let target = target_dir.join(entry.path().strip_prefix(&source_dir).unwrap());
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)?;
+        } else {
+            fs::create_dir_all(target.parent().unwrap())?;
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+
+    let recipe = target_dir.join(BUILD_FILE);
+    let (bumped, dropped) = authoring::bump_recipe(&fs::read_to_string(&recipe)?, &from, to);
+    fs::write(&recipe, bumped)?;
+
+    if let Err(e) = PackageParser::new(recipe.to_string_lossy()).parse() {
+        fs::remove_dir_all(&target_dir)?;
+        return Err(RegistryError::InvalidRecipe {
+            path: recipe.display().to_string(),
+            reason: e.to_string(),
+        });
+    }
+
+    for field in dropped {
+        progress(RegistryEvent::DroppedPin { field });
+    }
+    progress(RegistryEvent::WroteRecipe {
+        path: recipe.display().to_string(),
+    });
+    Ok(recipe)
+}
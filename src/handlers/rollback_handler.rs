@@ -0,0 +1,119 @@
+use std::path::Path;
+
+use crate::{
+    build::{
+        package::package::{InstalledBuild, InstalledPackage},
+        package_tracker::package_tracker::PackageTracker,
+        staging::staging_handler::{StagingHandler, remove_manifest_files},
+    },
+    handlers::{
+        events::RollbackEvent,
+        install_handler::{InstallError, find_conflicts},
+    },
+    util::config::Config,
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum RollbackError {
+    #[error("package not installed: {0}")]
+    NotInstalled(String),
+    #[error("no previous build of {0} to roll back to")]
+    NoPreviousVersion(String),
+    #[error("no saved build of {name}@{version}")]
+    VersionNotInHistory { name: String, version: String },
+    #[error("saved build is missing: {0}")]
+    ArchiveMissing(String),
+    #[error("{0}")]
+    InstallError(#[from] InstallError),
+}
+
+pub fn select_build<'a>(
+    tracker: &'a PackageTracker,
+    name: &str,
+    to: Option<&str>,
+) -> Result<&'a InstalledBuild, RollbackError> {
+    let installed = tracker
+        .get_package(name)
+        .ok_or_else(|| RollbackError::NotInstalled(name.to_string()))?;
+    let history = tracker.history(name);
+
+    match to {
+        Some(version) => history
+            .iter()
+            .find(|b| b.version == version)
+            .ok_or_else(|| RollbackError::VersionNotInHistory {
+                name: name.to_string(),
+                version: version.to_string(),
+            }),
+        None => history
+            .iter()
+            .rev()
+            .find(|b| b.version != installed.version)
+            .ok_or_else(|| RollbackError::NoPreviousVersion(name.to_string())),
+    }
+}
+
+pub fn run<F: FnMut(RollbackEvent)>(
+    config: &Config,
+    tracker: &mut PackageTracker,
+    name: &str,
+    to: Option<&str>,
+    overwrite: bool,
+    progress: &mut F,
+) -> Result<String, RollbackError> {
+    let build = select_build(tracker, name, to)?.clone();
+    let current = tracker.get_package(name).unwrap().clone();
+
+    progress(RollbackEvent::RollingBack {
+        name: name.to_string(),
+        from: current.version.clone(),
+        to: build.version.clone(),
+    });
+
+    if !Path::new(&build.archive).is_file() {
+        return Err(RollbackError::ArchiveMissing(build.archive));
+    }
+
+    let staging = StagingHandler::from_archive("/", Path::new(&build.archive))
+        .map_err(|e| InstallError::StagingError(e.to_string()))?;
+
+    if !overwrite {
+        let files = staging
+            .staged_files()
+            .map_err(|e| InstallError::StagingError(e.to_string()))?;
+        let conflicts = find_conflicts(name, &files, tracker);
+        if !conflicts.is_empty() {
+            return Err(InstallError::FileConflicts(conflicts).into());
+        }
+    }
+
+    progress(RollbackEvent::RestoringFiles);
+    let manifest = staging
+        .install(&mut |event| progress(RollbackEvent::Install(event)))
+        .map_err(|e| InstallError::StagingError(e.to_string()))?;
+
+    let stale = current.manifest.difference(&manifest);
+    if !stale.is_empty() {
+        remove_manifest_files(&stale).map_err(|e| InstallError::StagingError(e.to_string()))?;
+    }
+
+    tracker.take_ownership(name, &manifest.files);
+    tracker.add_package(InstalledPackage {
+        name: name.to_string(),
+        version: build.version.clone(),
+        install_path: build.install_path.clone(),
+        dependencies: build.dependencies.clone(),
+        manifest,
+    });
+    let version = build.version.clone();
+    for dropped in tracker.record_build(name, build, config.history_limit) {
+        let _ = std::fs::remove_file(dropped.archive);
+    }
+
+    progress(RollbackEvent::Finished {
+        name: name.to_string(),
+        version: version.clone(),
+    });
+    Ok(version)
+}
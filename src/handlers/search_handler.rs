@@ -0,0 +1,141 @@
+use crate::{
+    build::{
+        package_tracker::package_tracker::PackageTracker,
+        registry::registry_handler::{PackageEntry, Registry},
+    },
+    handlers::events::SearchEvent,
+};
+use regex::{Regex, RegexBuilder};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SearchError {
+    #[error("invalid regex: {0}")]
+    InvalidRegex(#[from] regex::Error),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    pub name: String,
+    pub version: String,
+    pub description: Option<String>,
+    pub installed: Option<String>,
+}
+
+enum Matcher {
+    Text(String),
+    Regex(Regex),
+}
+
+impl Matcher {
+    /// Scores how well `entry` matches; name matches rank above keyword
+    /// matches, which rank above description matches.
+    fn score(&self, name: &str, entry: &PackageEntry) -> Option<u32> {
+        let metadata = &entry.metadata;
+        let keywords = || metadata.keywords.iter().chain(&metadata.provides);
+
+        match self {
+            Matcher::Regex(regex) => {
+                if regex.is_match(name) {
+                    Some(300)
+                } else if keywords().any(|k| regex.is_match(k)) {
+                    Some(200)
+                } else if metadata
+                    .description
+                    .as_deref()
+                    .is_some_and(|d| regex.is_match(d))
+                {
+                    Some(100)
+                } else {
+                    None
+                }
+            }
+            Matcher::Text(query) => {
+                let name = name.to_lowercase();
+                let description = metadata.description.as_deref().unwrap_or_default();
+
+                if name == *query {
+                    Some(1000)
+                } else if name.starts_with(query.as_str()) {
+                    Some(800)
+                } else if name.contains(query.as_str()) {
+                    Some(600)
+                } else if keywords().any(|k| k.to_lowercase() == *query) {
+                    Some(500)
+                } else if keywords().any(|k| k.to_lowercase().contains(query.as_str())) {
+                    Some(400)
+                } else if let Some(score) = fuzzy_score(query, &name) {
+                    Some(200 + score)
+                } else if description.to_lowercase().contains(query.as_str()) {
+                    Some(100)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+/// Scores `needle` as a subsequence of `haystack`, up to 100 when the
+/// matched characters are adjacent and less the more they are spread out.
+fn fuzzy_score(needle: &str, haystack: &str) -> Option<u32> {
+    let mut chars = haystack.char_indices();
+    let mut first = None;
+    let mut last = 0;
+
+    for wanted in needle.chars() {
+        let (index, _) = chars.by_ref().find(|(_, c)| *c == wanted)?;
+        first.get_or_insert(index);
+        last = index;
+    }
+
+    let span = last - first? + 1;
+    let gaps = (span - needle.chars().count()) as u32;
+    Some(100u32.saturating_sub(gaps * 10))
+}
+
+pub fn run<F: FnMut(SearchEvent)>(
+    registry: &Registry,
+    tracker: &PackageTracker,
+    query: &str,
+    regex: bool,
+    progress: &mut F,
+) -> Result<Vec<SearchResult>, SearchError> {
+    let matcher = if regex {
+        Matcher::Regex(RegexBuilder::new(query).case_insensitive(true).build()?)
+    } else {
+        Matcher::Text(query.to_lowercase())
+    };
+
+    progress(SearchEvent::Searching {
+        query: query.to_string(),
+    });
+
+    let mut scored: Vec<(u32, &String, &PackageEntry)> = registry
+        .get_packages()
+        .iter()
+        .filter_map(|(name, entry)| Some((matcher.score(name, entry)?, name, entry)))
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+
+    let results: Vec<SearchResult> = scored
+        .into_iter()
+        .map(|(_, name, entry)| SearchResult {
+            name: name.clone(),
+            version: entry.latest.clone(),
+            description: entry.metadata.description.clone(),
+            installed: tracker.get_package(name).map(|p| p.version.clone()),
+        })
+        .collect();
+
+    if results.is_empty() {
+        progress(SearchEvent::NoResults {
+            query: query.to_string(),
+        });
+    }
+    for result in &results {
+        progress(SearchEvent::Found(result.clone()));
+    }
+
+    Ok(results)
+}
@@ -26,6 +26,44 @@ pub fn run<F: FnMut(ShowEvent)>(ctx: &Context, package_name: &str, progress: &mu
         versions: versions.clone(),
     });
 
+    progress(ShowEvent::Origin {
+        registry: pkg_entry.registry.clone(),
+        shadowed: pkg_entry.shadowed.clone(),
+    });
+
+    let metadata = &pkg_entry.metadata;
+    if let Some(text) = &metadata.description {
+        progress(ShowEvent::Description { text: text.clone() });
+    }
+    if let Some(license) = &metadata.license {
+        progress(ShowEvent::License {
+            license: license.clone(),
+        });
+    }
+    if let Some(url) = &metadata.homepage {
+        progress(ShowEvent::Homepage { url: url.clone() });
+    }
+    if !metadata.maintainers.is_empty() {
+        progress(ShowEvent::Maintainers {
+            maintainers: metadata.maintainers.clone(),
+        });
+    }
+    if !metadata.keywords.is_empty() {
+        progress(ShowEvent::Keywords {
+            keywords: metadata.keywords.clone(),
+        });
+    }
+    if !metadata.categories.is_empty() {
+        progress(ShowEvent::Categories {
+            categories: metadata.categories.clone(),
+        });
+    }
+    if !metadata.provides.is_empty() {
+        progress(ShowEvent::Provides {
+            names: metadata.provides.clone(),
+        });
+    }
+
     if let Some(installed_pkg) = ctx.tracker.get_package(package_name) {
         progress(ShowEvent::InstalledVersion {
             version: installed_pkg.version.clone(),
@@ -0,0 +1,57 @@
+use crate::{
+    build::registry::{registry_handler::Registry, remote::RemoteSource},
+    handlers::events::SyncEvent,
+    util::config::{Config, ConfigError},
+};
+
+/// Pulls every configured remote into the registry dir and rebuilds the
+/// index. A remote that fails to sync keeps its previous contents.
+pub fn run<F: FnMut(SyncEvent)>(
+    config: &Config,
+    progress: &mut F,
+) -> Result<Registry, ConfigError> {
+    let sources = config.registry_sources()?;
+
+    for remote in &config.registries {
+        if matches!(remote.source, RemoteSource::Local { .. }) {
+            continue;
+        }
+
+        progress(SyncEvent::SyncingRemote {
+            name: remote.name.clone(),
+            url: remote.url(),
+        });
+        if remote.allow_unsigned {
+            progress(SyncEvent::UnsignedRemote {
+                name: remote.name.clone(),
+            });
+        }
+
+        if let Err(e) = remote.sync(&config.registry_path, &config.trusted_keys) {
+            progress(SyncEvent::RemoteFailed {
+                name: remote.name.clone(),
+                error: e.to_string(),
+            });
+        }
+    }
+
+    progress(SyncEvent::Indexing);
+    let registry = Registry::resync_sources_and_save(&config.index_path, &sources, &config.pins);
+
+    for (package, pinned) in &config.pins {
+        if let Some(entry) = registry.get_package(package)
+            && entry.registry != *pinned
+        {
+            progress(SyncEvent::PinNotSatisfied {
+                package: package.clone(),
+                pinned: pinned.clone(),
+                used: entry.registry.clone(),
+            });
+        }
+    }
+
+    progress(SyncEvent::Finished {
+        packages: registry.get_packages().len(),
+    });
+    Ok(registry)
+}
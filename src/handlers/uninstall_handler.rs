@@ -2,9 +2,10 @@ use std::{fs, path::Path};
 
 use crate::{
     build::{
-        package::package::{Dependency, InstalledPackage},
+        package::package::{Dependency, DependencySource, InstalledPackage},
         package_manager::manager::{PackageManager, PackageManagerApi},
         package_tracker::package_tracker::PackageTracker,
+        staging::staging_handler::remove_manifest_files,
     },
     handlers::events::UninstallEvent,
 };
@@ -36,7 +37,13 @@ pub fn plan(tracker: &PackageTracker, package: &str) -> Result<UninstallPlan, Un
     let mut keep = Vec::new();
 
     for dep in &installed.dependencies {
-        if is_dependency_used_by_others(dep) < 1 || tracker.dependency_usage_count(&dep.name) < 1 {
+        // Reponere-managed dependencies are packages in their own right and
+        // are left for the user to uninstall explicitly.
+        if dep.source == Some(DependencySource::Reponere) {
+            keep.push(dep.clone());
+        } else if is_dependency_used_by_others(dep) < 1
+            || tracker.dependency_usage_count(&dep.name) < 1
+        {
             remove.push(dep.clone());
         } else {
             keep.push(dep.clone());
@@ -59,7 +66,7 @@ pub fn execute<F: FnMut(UninstallEvent)>(
     uninstall_dependencies(plan.remove_dependencies, progress)?;
 
     progress(UninstallEvent::RemovingPackageFiles);
-    remove_package_files(&plan.package)?;
+    remove_package_files(&plan.package, progress)?;
 
     progress(UninstallEvent::Cleanup);
     tracker.remove_package(&plan.package.name);
@@ -91,19 +98,21 @@ fn is_dependency_used_by_others(dep: &Dependency) -> usize {
     pm.reverse_dependencies(&dep.name).unwrap().len()
 }
 
-fn remove_package_files(package: &InstalledPackage) -> Result<(), UninstallError> {
-    let path = Path::new(&package.install_path);
-
-    if !path.exists() {
-        return Err(UninstallError::Io(std::io::Error::from(
-            std::io::ErrorKind::NotFound,
-        )));
+fn remove_package_files<F: FnMut(UninstallEvent)>(
+    package: &InstalledPackage,
+    progress: &mut F,
+) -> Result<(), UninstallError> {
+    for file in &package.manifest.files {
+        progress(UninstallEvent::RemovingFile { path: file.clone() });
     }
 
-    if path.is_dir() {
-        fs::remove_dir_all(path).map_err(UninstallError::Io)?;
-    } else {
-        fs::remove_file(path).map_err(UninstallError::Io)?;
+    remove_manifest_files(&package.manifest).map_err(UninstallError::Io)?;
+
+    // Entries recorded before manifests existed only know their install path;
+    // remove it when it is a single file, but never a whole directory.
+    let legacy = Path::new(&package.install_path);
+    if package.manifest.files.is_empty() && legacy.is_file() {
+        fs::remove_file(legacy).map_err(UninstallError::Io)?;
     }
 
     Ok(())
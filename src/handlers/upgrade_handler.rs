@@ -0,0 +1,108 @@
+use crate::{
+    build::{
+        dependency_handler::version::{VersionError, is_newer},
+        package_tracker::package_tracker::PackageTracker,
+        registry::registry_handler::Registry,
+    },
+    handlers::{
+        events::UpgradeEvent,
+        install_handler::{self, InstallError, InstallOptions},
+    },
+    util::config::Config,
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum UpgradeError {
+    #[error("package not installed: {0}")]
+    NotInstalled(String),
+    #[error("package not found in registry: {0}")]
+    PackageNotFound(String),
+    #[error("{0}")]
+    InstallError(#[from] InstallError),
+    #[error("{0}")]
+    InvalidVersion(#[from] VersionError),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpgradeCandidate {
+    pub name: String,
+    pub current: String,
+    pub latest: String,
+}
+
+pub fn plan(
+    reg: &Registry,
+    tracker: &PackageTracker,
+    packages: &[String],
+) -> Result<Vec<UpgradeCandidate>, UpgradeError> {
+    let names: Vec<String> = if packages.is_empty() {
+        let mut names: Vec<String> = tracker.get_packages().keys().cloned().collect();
+        names.sort();
+        names
+    } else {
+        packages.to_vec()
+    };
+
+    let mut candidates = Vec::new();
+    for name in names {
+        let installed = tracker
+            .get_package(&name)
+            .ok_or_else(|| UpgradeError::NotInstalled(name.clone()))?;
+
+        let entry = match reg.get_package(&name) {
+            Some(entry) => entry,
+            // Packages installed from a registry that no longer lists them
+            // cannot be upgraded, but should not block upgrading the rest.
+            None if packages.is_empty() => continue,
+            None => return Err(UpgradeError::PackageNotFound(name)),
+        };
+
+        if is_newer(&entry.latest, &installed.version)? {
+            candidates.push(UpgradeCandidate {
+                name,
+                current: installed.version.clone(),
+                latest: entry.latest.clone(),
+            });
+        }
+    }
+
+    Ok(candidates)
+}
+
+pub fn execute<F: FnMut(UpgradeEvent)>(
+    config: &Config,
+    reg: &Registry,
+    tracker: &mut PackageTracker,
+    candidate: &UpgradeCandidate,
+    overwrite: bool,
+    progress: &mut F,
+) -> Result<(), UpgradeError> {
+    progress(UpgradeEvent::Upgrading {
+        name: candidate.name.clone(),
+        from: candidate.current.clone(),
+        to: candidate.latest.clone(),
+    });
+
+    // The install handler builds into a staging directory and only renames
+    // files into place once the build and every copy succeeded, so a
+    // failure here leaves the previously installed version untouched.
+    install_handler::run(
+        config,
+        reg,
+        tracker,
+        &format!("{}@{}", candidate.name, candidate.latest),
+        InstallOptions {
+            force: true,
+            overwrite,
+            lockfile: None,
+            solution: None,
+        },
+        &mut |event| progress(UpgradeEvent::Install(event)),
+    )?;
+
+    progress(UpgradeEvent::Finished {
+        name: candidate.name.clone(),
+    });
+    Ok(())
+}
@@ -1,9 +1,4 @@
-mod build;
-mod cli;
-mod handlers;
-mod util;
-
-use crate::{cli::commands, util::context::Context};
+use reponere::{cli::commands, util::context::Context};
 
 fn main() {
     let mut ctx = Context::new().unwrap_or_else(|e| {
@@ -1,26 +1,104 @@
 use serde::{Deserialize, Serialize};
 
+use crate::build::registry::{
+    registry_handler::{LOCAL_REGISTRY, RegistrySource},
+    remote::{RemoteError, RemoteRegistry},
+};
 use dirs;
-use std::{fs, path::PathBuf};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
 use toml;
 
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("registry {name} is invalid: {source}")]
+    InvalidRegistry { name: String, source: RemoteError },
+
+    #[error("{package} is pinned to {registry}, which is not a configured registry")]
+    UnknownPinRegistry { package: String, registry: String },
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
     pub index_path: PathBuf,
     pub registry_path: PathBuf,
     pub packages_path: PathBuf,
+    #[serde(default = "Config::default_cache_path")]
+    pub cache_path: PathBuf,
+    #[serde(default = "Config::default_history_path")]
+    pub history_path: PathBuf,
+    #[serde(default = "Config::default_history_limit")]
+    pub history_limit: usize,
+    #[serde(default = "Config::default_log_path")]
+    pub log_path: PathBuf,
+    #[serde(default, alias = "remotes")]
+    pub registries: Vec<RemoteRegistry>,
+    /// Package name to the registry it must always be taken from.
+    #[serde(default)]
+    pub pins: BTreeMap<String, String>,
+    /// ssh public keys that may sign remote registries.
+    #[serde(default)]
+    pub trusted_keys: Vec<String>,
+    /// Run build steps in a namespace sandbox, with network access only for
+    /// recipes that ask for it.
+    #[serde(default = "Config::default_sandbox")]
+    pub sandbox: bool,
+    /// Run the check phase of recipes before installing.
+    #[serde(default)]
+    pub checks: bool,
+    /// Where packages are installed, exposed to builds as `PREFIX`.
+    #[serde(default = "Config::default_prefix")]
+    pub prefix: PathBuf,
+    /// Parallel jobs exposed to builds as `JOBS`; defaults to the CPU count.
+    #[serde(default)]
+    pub jobs: Option<usize>,
+    /// Variables such as CFLAGS or RUSTFLAGS that override recipe values.
+    #[serde(default)]
+    pub build_env: BTreeMap<String, String>,
+    /// Variables passed through to builds in addition to the built-in list.
+    #[serde(default)]
+    pub env_passthrough: Vec<String>,
 }
 
 impl Config {
+    /// A config keeping all of its files below `root`, with every other
+    /// setting at its default.
+    pub fn for_root(root: &Path) -> Self {
+        Config {
+            index_path: root.join("index.json"),
+            registry_path: root.join("registry"),
+            packages_path: root.join("packages.json"),
+            cache_path: root.join("cache"),
+            history_path: root.join("history"),
+            history_limit: Config::default_history_limit(),
+            log_path: root.join("logs"),
+            registries: Vec::new(),
+            pins: BTreeMap::new(),
+            trusted_keys: Vec::new(),
+            sandbox: Config::default_sandbox(),
+            checks: false,
+            prefix: root.join("prefix"),
+            jobs: None,
+            build_env: BTreeMap::new(),
+            env_passthrough: Vec::new(),
+        }
+    }
+
     fn create_default() -> Result<String, Box<dyn std::error::Error>> {
         let home = dirs::home_dir().expect("Unable to get home directory");
         let config_dir = home.join(".config/reponere");
         fs::create_dir_all(&config_dir)?;
 
         let config = Config {
-            index_path: home.join(".config/reponere/index.json"),
-            registry_path: home.join(".config/reponere/registry"),
-            packages_path: home.join(".config/reponere/packages.json"),
+            cache_path: Config::default_cache_path(),
+            history_path: Config::default_history_path(),
+            log_path: Config::default_log_path(),
+            prefix: Config::default_prefix(),
+            ..Config::for_root(&config_dir)
         };
         let serialized = toml::to_string(&config)?;
         std::fs::write(
@@ -30,6 +108,72 @@ impl Config {
         Ok(serialized.clone())
     }
 
+    fn default_cache_path() -> PathBuf {
+        let home = dirs::home_dir().expect("Unable to get home directory");
+        home.join(".cache/reponere")
+    }
+
+    fn default_history_path() -> PathBuf {
+        let home = dirs::home_dir().expect("Unable to get home directory");
+        home.join(".local/share/reponere/history")
+    }
+
+    fn default_log_path() -> PathBuf {
+        let home = dirs::home_dir().expect("Unable to get home directory");
+        home.join(".local/share/reponere/logs")
+    }
+
+    fn default_history_limit() -> usize {
+        3
+    }
+
+    fn default_sandbox() -> bool {
+        true
+    }
+
+    fn default_prefix() -> PathBuf {
+        let home = dirs::home_dir().expect("Unable to get home directory");
+        home.join(".local")
+    }
+
+    pub fn jobs(&self) -> usize {
+        self.jobs.unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+        })
+    }
+
+    /// The local registry dir followed by every configured registry, after
+    /// checking that every pin names one of them.
+    pub fn registry_sources(&self) -> Result<Vec<RegistrySource>, ConfigError> {
+        let mut sources = vec![RegistrySource {
+            name: LOCAL_REGISTRY.to_string(),
+            path: self.registry_path.clone(),
+            priority: 0,
+        }];
+
+        for registry in &self.registries {
+            let source = registry
+                .registry_source(&self.registry_path)
+                .map_err(|source| ConfigError::InvalidRegistry {
+                    name: registry.name.clone(),
+                    source,
+                })?;
+            sources.push(source);
+        }
+
+        for (package, registry) in &self.pins {
+            if !sources.iter().any(|s| s.name == *registry) {
+                return Err(ConfigError::UnknownPinRegistry {
+                    package: package.clone(),
+                    registry: registry.clone(),
+                });
+            }
+        }
+        Ok(sources)
+    }
+
     pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
         let home = dirs::home_dir().expect("Unable to get home directory");
         let config_path = home.join(".config/reponere/config.toml");
@@ -13,7 +13,11 @@ impl Context {
     pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
         let config = Config::load()?;
 
-        let registry = Registry::load_or_sync(&config.index_path, &config.registry_path);
+        let registry = Registry::load_or_sync_sources(
+            &config.index_path,
+            &config.registry_sources()?,
+            &config.pins,
+        );
 
         let tracker =
             PackageTracker::load(config.packages_path.to_str().unwrap()).unwrap_or_default();
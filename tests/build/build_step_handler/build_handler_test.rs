@@ -1,17 +1,38 @@
 mod tests {
-    use std::path::Path;
+    use std::{fs, path::Path};
 
     use reponere::build::build_step_handler::build_handler::{BuildHandler, BuildHandlerError};
-    use reponere::build::package::package::Build;
+    use reponere::build::build_step_handler::environment::BuildEnvironment;
+    use reponere::build::build_step_handler::sandbox::{Sandbox, SandboxTool};
+    use reponere::build::package::package::{Build, BuildHelper};
+    use reponere::handlers::events::InstallEvent;
+    use tempfile::TempDir;
+
+    fn sandboxed(steps: &[&str]) -> Option<(BuildHandler, TempDir)> {
+        let Some(tool) = SandboxTool::detect() else {
+            eprintln!("no sandbox tool available, skipping");
+            return None;
+        };
+
+        let destdir = TempDir::new().unwrap();
+        let handler = BuildHandler::new(Build {
+            steps: steps.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        })
+        .with_destdir(destdir.path())
+        .with_sandbox(Sandbox::new(tool));
+        Some((handler, destdir))
+    }
 
     #[test]
     fn test_run_build_steps_success() {
         let build = Build {
             steps: vec!["echo hello".to_string()],
+            ..Default::default()
         };
         let handler = BuildHandler::new(build);
 
-        let result = handler.run_build_steps(Path::new("."));
+        let result = handler.run_build_steps(Path::new("."), &mut |_| {});
         assert!(result.is_ok());
     }
 
@@ -19,21 +40,263 @@ mod tests {
     fn test_run_build_steps_failure() {
         let build = Build {
             steps: vec!["false".to_string()],
+            ..Default::default()
         };
         let handler = BuildHandler::new(build);
 
-        let result = handler.run_build_steps(Path::new("."));
-        assert!(matches!(result, Err(BuildHandlerError::BuildStepFailed(_))));
+        let result = handler.run_build_steps(Path::new("."), &mut |_| {});
+        assert!(matches!(
+            result,
+            Err(BuildHandlerError::BuildStepFailed { .. })
+        ));
     }
 
     #[test]
     fn test_run_multiple_steps() {
         let build = Build {
             steps: vec!["echo step1".to_string(), "echo step2".to_string()],
+            ..Default::default()
         };
         let handler = BuildHandler::new(build);
 
-        let result = handler.run_build_steps(Path::new("."));
+        let result = handler.run_build_steps(Path::new("."), &mut |_| {});
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_sandbox_allows_source_dir_and_destdir() {
+        let source = TempDir::new().unwrap();
+        let Some((handler, destdir)) = sandboxed(&[
+            "echo built > artifact",
+            "mkdir -p \"$DESTDIR/bin\" && cp artifact \"$DESTDIR/bin/\"",
+            "echo scratch > \"$TMPDIR/scratch\"",
+        ]) else {
+            return;
+        };
+
+        handler.run_build_steps(source.path(), &mut |_| {}).unwrap();
+        assert!(source.path().join("artifact").exists());
+        assert!(destdir.path().join("bin/artifact").exists());
+    }
+
+    #[test]
+    fn test_sandbox_blocks_writes_elsewhere() {
+        let source = TempDir::new().unwrap();
+        let home = TempDir::new().unwrap();
+        let target = home.path().join("pwned");
+
+        let step = format!("touch {}", target.display());
+        let Some((handler, _destdir)) = sandboxed(&[&step]) else {
+            return;
+        };
+
+        let result = handler.run_build_steps(source.path(), &mut |_| {});
+        assert!(matches!(
+            result,
+            Err(BuildHandlerError::BuildStepFailed { .. })
+        ));
+        assert!(!target.exists());
+        assert_eq!(fs::read_dir(home.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_sandbox_disables_network() {
+        let source = TempDir::new().unwrap();
+        // only the loopback interface exists in a fresh network namespace
+        let Some((handler, _destdir)) = sandboxed(&["test $(grep -c : /proc/net/dev) -eq 1"])
+        else {
+            return;
+        };
+
+        handler.run_build_steps(source.path(), &mut |_| {}).unwrap();
+    }
+
+    #[test]
+    fn test_sandboxed_cargo_helper_build() {
+        let Some(tool) = SandboxTool::detect() else {
+            eprintln!("no sandbox tool available, skipping");
+            return;
+        };
+        let source = TempDir::new().unwrap();
+        let destdir = TempDir::new().unwrap();
+        fs::create_dir_all(source.path().join("src")).unwrap();
+        fs::write(
+            source.path().join("Cargo.toml"),
+            "[package]\nname = \"hello\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .unwrap();
+        fs::write(
+            source.path().join("Cargo.lock"),
+            "version = 3\n\n[[package]]\nname = \"hello\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        fs::write(source.path().join("src/main.rs"), "fn main() {}").unwrap();
+
+        let handler = BuildHandler::new(Build {
+            helper: Some(BuildHelper::Cargo),
+            // what cargo does when it downloads dependencies
+            prepare: Some(vec![
+                "mkdir -p \"${CARGO_HOME:-$HOME/.cargo}/registry\" && \
+                 touch \"${CARGO_HOME:-$HOME/.cargo}/registry/.probe\""
+                    .to_string(),
+            ]),
+            ..Default::default()
+        })
+        .with_destdir(destdir.path())
+        .with_environment(
+            BuildEnvironment::new::<&str>(&[])
+                .with_var("PREFIX", "/usr")
+                .with_var("JOBS", "1"),
+        )
+        .with_sandbox(Sandbox::new(tool).with_network(true));
+
+        let mut output = Vec::new();
+        let result = handler.run_build_steps(source.path(), &mut |event| {
+            if let InstallEvent::BuildOutput { line } = event {
+                output.push(line);
+            }
+        });
+        assert!(result.is_ok(), "{}", output.join("\n"));
+        assert!(destdir.path().join("usr/bin/hello").exists());
+    }
+
+    #[test]
+    fn test_build_environment_layers() {
+        let source = TempDir::new().unwrap();
+        let build = Build {
+            steps: vec!["env > vars.txt".to_string()],
+            env: [
+                ("CFLAGS".to_string(), "-O2".to_string()),
+                ("LDFLAGS".to_string(), "-s".to_string()),
+                ("PREFIX".to_string(), "/recipe".to_string()),
+            ]
+            .into(),
+            ..Default::default()
+        };
+        let overrides = [
+            ("CFLAGS".to_string(), "-O3".to_string()),
+            ("JOBS".to_string(), "64".to_string()),
+        ]
+        .into();
+        let handler = BuildHandler::new(build).with_environment(
+            BuildEnvironment::new::<&str>(&[])
+                .with_overrides(&overrides)
+                .with_var("PREFIX", "/opt/pkg")
+                .with_var("JOBS", "2"),
+        );
+
+        handler.run_build_steps(source.path(), &mut |_| {}).unwrap();
+
+        let vars = fs::read_to_string(source.path().join("vars.txt")).unwrap();
+        let lines: Vec<&str> = vars.lines().collect();
+        assert!(lines.contains(&"CFLAGS=-O3"));
+        assert!(lines.contains(&"LDFLAGS=-s"));
+        assert!(lines.contains(&"PREFIX=/opt/pkg"));
+        assert!(lines.contains(&"JOBS=2"));
+        assert!(lines.contains(&format!("SRCDIR={}", source.path().display()).as_str()));
+        assert!(!lines.iter().any(|l| l.starts_with("CARGO_PKG_NAME=")));
+    }
+
+    #[test]
+    fn test_build_output_streamed_and_logged() {
+        let logs = TempDir::new().unwrap();
+        let log = logs.path().join("tool/1-1.0.0.log");
+        let build = Build {
+            steps: vec!["echo out; echo err >&2".to_string()],
+            ..Default::default()
+        };
+        let handler = BuildHandler::new(build).with_log(&log);
+
+        let mut output = Vec::new();
+        handler
+            .run_build_steps(Path::new("."), &mut |event| {
+                if let InstallEvent::BuildOutput { line } = event {
+                    output.push(line);
+                }
+            })
+            .unwrap();
+
+        output.sort();
+        assert_eq!(output, ["err", "out"]);
+        let contents = fs::read_to_string(&log).unwrap();
+        assert!(contents.starts_with("==> build phase\n-> echo out; echo err >&2\n"));
+        assert!(contents.contains("out\n") && contents.contains("err\n"));
+    }
+
+    #[test]
+    fn test_failure_includes_tail_and_log() {
+        let logs = TempDir::new().unwrap();
+        let log = logs.path().join("tool.log");
+        let build = Build {
+            steps: vec![
+                "echo first step".to_string(),
+                "seq 1 30; exit 1".to_string(),
+            ],
+            ..Default::default()
+        };
+        let handler = BuildHandler::new(build).with_log(&log);
+
+        let Err(BuildHandlerError::BuildStepFailed {
+            step,
+            tail,
+            log: path,
+        }) = handler.run_build_steps(Path::new("."), &mut |_| {})
+        else {
+            panic!("expected the second step to fail");
+        };
+
+        assert_eq!(step, "seq 1 30; exit 1");
+        let expected: Vec<String> = (11..=30).map(|n| n.to_string()).collect();
+        assert_eq!(tail, expected);
+        assert_eq!(path.as_deref(), Some(log.as_path()));
+        assert!(fs::read_to_string(&log).unwrap().contains("first step"));
+    }
+
+    #[test]
+    fn test_phases_run_in_order() {
+        let source = TempDir::new().unwrap();
+        let destdir = TempDir::new().unwrap();
+        fs::write(
+            source.path().join("Makefile"),
+            "all:\n\techo built > out\ncheck:\n\ttouch checked\n\
+             install:\n\tinstall -Dm644 out $(DESTDIR)$(PREFIX)/share/out\n",
+        )
+        .unwrap();
+        let build = Build {
+            helper: Some(BuildHelper::Make),
+            prepare: Some(vec!["echo prepared > prepared".to_string()]),
+            ..Default::default()
+        };
+        let handler = BuildHandler::new(build).with_destdir(destdir.path());
+
+        let mut phases = Vec::new();
+        handler
+            .run_build_steps(source.path(), &mut |event| {
+                if let InstallEvent::BuildPhase { phase } = event {
+                    phases.push(phase);
+                }
+            })
+            .unwrap();
+
+        assert_eq!(phases, ["prepare", "build", "install"]);
+        assert!(source.path().join("prepared").exists());
+        assert!(!source.path().join("checked").exists());
+        assert_eq!(
+            fs::read_to_string(destdir.path().join("share/out")).unwrap(),
+            "built\n"
+        );
+    }
+
+    #[test]
+    fn test_check_phase_runs_when_enabled() {
+        let source = TempDir::new().unwrap();
+        let build = Build {
+            check: Some(vec!["touch checked".to_string()]),
+            ..Default::default()
+        };
+        let handler = BuildHandler::new(build).with_checks(true);
+
+        handler.run_build_steps(source.path(), &mut |_| {}).unwrap();
+        assert!(source.path().join("checked").exists());
+    }
 }
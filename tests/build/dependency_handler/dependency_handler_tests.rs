@@ -54,7 +54,7 @@ impl PackageManagerApi for MockPackageManager {
         Ok(self.available.borrow().get(package).cloned())
     }
 
-    fn reverse_dependencies(&self, package: &str) -> Result<Vec<String>, PackageManagerError> {
+    fn reverse_dependencies(&self, _package: &str) -> Result<Vec<String>, PackageManagerError> {
         Ok(Vec::new())
     }
 }
@@ -74,6 +74,7 @@ mod tests {
         Dependency {
             name: name.to_string(),
             version_req: version_req.map(|s| s.to_string()),
+            source: None,
         }
     }
 
@@ -87,7 +88,7 @@ mod tests {
         let handler = DependencyHandler::new(&mock_pm, deps);
 
         let mut errors = Vec::new();
-        handler.install_runtime_dependencies(&mut errors);
+        handler.install_runtime_dependencies(&mut errors, &mut |_| {});
 
         assert!(errors.is_empty());
         // install method inserts "foo", but get_installed_version expects "foo@version"
@@ -107,7 +108,7 @@ mod tests {
         let handler = DependencyHandler::new(&mock_pm, deps);
 
         let mut errors = Vec::new();
-        let installed = handler.install_build_dependencies(&mut errors);
+        let installed = handler.install_build_dependencies(&mut errors, &mut |_| {});
 
         assert_eq!(installed, vec!["bar".to_string()]);
         assert!(errors.is_empty());
@@ -126,7 +127,7 @@ mod tests {
         let handler = DependencyHandler::new(&mock_pm, deps);
 
         let mut errors = Vec::new();
-        handler.install_runtime_dependencies(&mut errors);
+        handler.install_runtime_dependencies(&mut errors, &mut |_| {});
 
         assert_eq!(errors.len(), 1);
         match &errors[0] {
@@ -147,7 +148,7 @@ mod tests {
         let handler = DependencyHandler::new(&mock_pm, deps);
 
         let mut errors = Vec::new();
-        handler.install_runtime_dependencies(&mut errors);
+        handler.install_runtime_dependencies(&mut errors, &mut |_| {});
 
         assert!(errors.is_empty());
         let installed_version = mock_pm.get_installed_version("foo").unwrap();
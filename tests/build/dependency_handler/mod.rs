@@ -1,3 +1,4 @@
 pub mod build_dependency_guard_tests;
 pub mod dependency_handler_tests;
+pub mod reponere_resolver_tests;
 pub mod version_tests;
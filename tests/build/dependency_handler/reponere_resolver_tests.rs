@@ -0,0 +1,218 @@
+mod tests {
+    use reponere::build::dependency_handler::reponere_resolver::{ReponereResolver, ResolveError};
+    use reponere::build::package::package::Dependency;
+    use reponere::build::package::parse::PackageParser;
+    use reponere::build::registry::registry_handler::Registry;
+    use tempfile::TempDir;
+
+    use crate::common::{registry, write_package};
+
+    fn resolve(reg: &Registry, dir: &TempDir, name: &str, version: &str) -> Vec<String> {
+        let recipe = dir
+            .path()
+            .join(name)
+            .join(version)
+            .join("package_build.yaml");
+        let package = PackageParser::new(recipe.to_str().unwrap())
+            .parse()
+            .unwrap();
+
+        ReponereResolver::new(reg)
+            .resolve(&package)
+            .unwrap()
+            .into_iter()
+            .map(|p| format!("{}@{}", p.name, p.version))
+            .collect()
+    }
+
+    #[test]
+    fn test_resolve_topological_order() {
+        let dir = TempDir::new().unwrap();
+        write_package(
+            dir.path(),
+            "app",
+            "1.0.0",
+            &["{ name: lib }", "{ name: python3 }"],
+        );
+        write_package(dir.path(), "lib", "1.0.0", &["{ name: base }"]);
+        write_package(dir.path(), "base", "1.0.0", &[]);
+        let reg = registry(dir.path());
+
+        assert_eq!(
+            resolve(&reg, &dir, "app", "1.0.0"),
+            vec!["base@1.0.0", "lib@1.0.0"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_honors_version_requirement_and_source() {
+        let dir = TempDir::new().unwrap();
+        write_package(
+            dir.path(),
+            "app",
+            "1.0.0",
+            &[
+                "{ name: lib, version_req: \"<2.0.0\" }",
+                "{ name: base, source: system }",
+            ],
+        );
+        write_package(dir.path(), "lib", "1.5.0", &[]);
+        write_package(dir.path(), "lib", "2.0.0", &[]);
+        write_package(dir.path(), "base", "1.0.0", &[]);
+        let reg = registry(dir.path());
+
+        assert_eq!(resolve(&reg, &dir, "app", "1.0.0"), vec!["lib@1.5.0"]);
+    }
+
+    #[test]
+    fn test_resolve_detects_cycles() {
+        let dir = TempDir::new().unwrap();
+        write_package(dir.path(), "a", "1.0.0", &["{ name: b }"]);
+        write_package(dir.path(), "b", "1.0.0", &["{ name: a }"]);
+        let reg = registry(dir.path());
+
+        let recipe = dir.path().join("a/1.0.0/package_build.yaml");
+        let package = PackageParser::new(recipe.to_str().unwrap())
+            .parse()
+            .unwrap();
+
+        match ReponereResolver::new(&reg).resolve(&package) {
+            Err(ResolveError::Cycle(cycle)) => assert_eq!(cycle, vec!["a", "b", "a"]),
+            other => panic!("expected cycle, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_resolve_missing_reponere_dependency() {
+        let dir = TempDir::new().unwrap();
+        write_package(
+            dir.path(),
+            "app",
+            "1.0.0",
+            &["{ name: ghost, source: reponere }"],
+        );
+        let reg = registry(dir.path());
+
+        let recipe = dir.path().join("app/1.0.0/package_build.yaml");
+        let package = PackageParser::new(recipe.to_str().unwrap())
+            .parse()
+            .unwrap();
+
+        match ReponereResolver::new(&reg).resolve(&package) {
+            Err(ResolveError::NoSolution(conflicts)) => assert_eq!(
+                conflicts,
+                vec!["ghost is not in the registry (required by app@1.0.0)"]
+            ),
+            other => panic!("expected no solution, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_resolve_backtracks_to_older_release() {
+        let dir = TempDir::new().unwrap();
+        write_package(
+            dir.path(),
+            "app",
+            "1.0.0",
+            &["{ name: lib }", "{ name: tool }"],
+        );
+        write_package(dir.path(), "lib", "1.0.0", &["{ name: base }"]);
+        write_package(
+            dir.path(),
+            "lib",
+            "2.0.0",
+            &["{ name: base, version_req: \">=2.0.0\" }"],
+        );
+        write_package(
+            dir.path(),
+            "tool",
+            "1.0.0",
+            &["{ name: base, version_req: \"<2.0.0\" }"],
+        );
+        write_package(dir.path(), "base", "1.0.0", &[]);
+        write_package(dir.path(), "base", "2.0.0", &[]);
+        let reg = registry(dir.path());
+
+        assert_eq!(
+            resolve(&reg, &dir, "app", "1.0.0"),
+            vec!["base@1.0.0", "lib@1.0.0", "tool@1.0.0"]
+        );
+    }
+
+    #[test]
+    fn test_solve_explains_conflicts() {
+        let dir = TempDir::new().unwrap();
+        write_package(dir.path(), "lib", "1.0.0", &[]);
+        write_package(dir.path(), "lib", "2.0.0", &[]);
+        write_package(
+            dir.path(),
+            "tool",
+            "1.0.0",
+            &["{ name: lib, version_req: \"<2.0.0\" }"],
+        );
+        let reg = registry(dir.path());
+
+        let requests = vec![
+            Dependency {
+                name: "tool".to_string(),
+                version_req: None,
+                source: None,
+            },
+            Dependency {
+                name: "lib".to_string(),
+                version_req: Some(">=2.0.0".to_string()),
+                source: None,
+            },
+        ];
+
+        match ReponereResolver::new(&reg).solve(&requests) {
+            Err(ResolveError::NoSolution(conflicts)) => assert_eq!(
+                conflicts,
+                vec![
+                    "no release of lib satisfies all requirements:\n  \
+                     - >=2.0.0 (required by command line)\n  \
+                     - <2.0.0 (required by tool@1.0.0)\n  \
+                     available: 2.0.0, 1.0.0"
+                ]
+            ),
+            other => panic!("expected no solution, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_solve_reports_only_the_final_path() {
+        let dir = TempDir::new().unwrap();
+        write_package(
+            dir.path(),
+            "app",
+            "1.0.0",
+            &["{ name: lib, version_req: \">=2.0.0\" }"],
+        );
+        write_package(
+            dir.path(),
+            "app",
+            "2.0.0",
+            &["{ name: lib, version_req: \">=3.0.0\" }"],
+        );
+        write_package(dir.path(), "lib", "1.0.0", &[]);
+        let reg = registry(dir.path());
+
+        let requests = vec![Dependency {
+            name: "app".to_string(),
+            version_req: None,
+            source: None,
+        }];
+
+        match ReponereResolver::new(&reg).solve(&requests) {
+            Err(ResolveError::NoSolution(conflicts)) => assert_eq!(
+                conflicts,
+                vec![
+                    "no release of lib satisfies all requirements:\n  \
+                     - >=2.0.0 (required by app@1.0.0)\n  \
+                     available: 1.0.0"
+                ]
+            ),
+            other => panic!("expected no solution, got {other:?}"),
+        }
+    }
+}
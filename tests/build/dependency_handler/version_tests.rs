@@ -1,39 +1,121 @@
 mod tests {
-    use reponere::build::dependency_handler::version::VersionRequirement;
+    use reponere::build::dependency_handler::version::{
+        VersionError, VersionRequirement, is_newer,
+    };
 
     #[test]
     fn test_matches() {
-        let req = VersionRequirement::parse_requirement("==1.2.3");
-        assert!(req.matches("1.2.3"));
-        assert!(!req.matches("1.2.4"));
+        let req = VersionRequirement::parse_requirement("==1.2.3").unwrap();
+        assert!(req.matches("1.2.3").unwrap());
+        assert!(!req.matches("1.2.4").unwrap());
 
-        let req = VersionRequirement::parse_requirement(">1.2.3");
-        assert!(req.matches("1.2.4"));
-        assert!(!req.matches("1.2.3"));
+        let req = VersionRequirement::parse_requirement(">1.2.3").unwrap();
+        assert!(req.matches("1.2.4").unwrap());
+        assert!(!req.matches("1.2.3").unwrap());
 
-        let req = VersionRequirement::parse_requirement(">=1.2.3");
-        assert!(req.matches("1.2.3"));
-        assert!(req.matches("1.2.4"));
-        assert!(!req.matches("1.2.2"));
+        let req = VersionRequirement::parse_requirement(">=1.2.3").unwrap();
+        assert!(req.matches("1.2.3").unwrap());
+        assert!(req.matches("1.2.4").unwrap());
+        assert!(!req.matches("1.2.2").unwrap());
 
-        let req = VersionRequirement::parse_requirement("<2.0.0");
-        assert!(req.matches("1.9.9"));
-        assert!(!req.matches("2.0.0"));
+        let req = VersionRequirement::parse_requirement("<2.0.0").unwrap();
+        assert!(req.matches("1.9.9").unwrap());
+        assert!(!req.matches("2.0.0").unwrap());
 
-        let req = VersionRequirement::parse_requirement("<=2.0.0");
-        assert!(req.matches("2.0.0"));
-        assert!(req.matches("1.9.9"));
-        assert!(!req.matches("2.0.1"));
+        let req = VersionRequirement::parse_requirement("<=2.0.0").unwrap();
+        assert!(req.matches("2.0.0").unwrap());
+        assert!(req.matches("1.9.9").unwrap());
+        assert!(!req.matches("2.0.1").unwrap());
 
         // No operator defaults to ==
-        let req = VersionRequirement::parse_requirement("3.3.3");
-        assert!(req.matches("3.3.3"));
-        assert!(!req.matches("3.3.4"));
+        let req = VersionRequirement::parse_requirement("3.3.3").unwrap();
+        assert!(req.matches("3.3.3").unwrap());
+        assert!(!req.matches("3.3.4").unwrap());
     }
 
     #[test]
     fn test_invalid_version_strings() {
-        let req = VersionRequirement::parse_requirement(">=1.0.0");
-        assert!(!req.matches("not-a-version"));
+        let req = VersionRequirement::parse_requirement(">=1.0.0").unwrap();
+        assert!(matches!(
+            req.matches("not-a-version"),
+            Err(VersionError::InvalidVersion(_))
+        ));
+    }
+
+    #[test]
+    fn test_compound_and_range_operators() {
+        let req = VersionRequirement::parse_requirement(">=1.2, <2.0").unwrap();
+        assert!(req.matches("1.2.0").unwrap());
+        assert!(req.matches("1.9.9").unwrap());
+        assert!(!req.matches("2.0.0").unwrap());
+        assert!(!req.matches("1.1.9").unwrap());
+
+        let req = VersionRequirement::parse_requirement("^1.4").unwrap();
+        assert!(req.matches("1.4.0").unwrap());
+        assert!(req.matches("1.9.3").unwrap());
+        assert!(!req.matches("2.0.0").unwrap());
+
+        let req = VersionRequirement::parse_requirement("^0.4.2").unwrap();
+        assert!(req.matches("0.4.9").unwrap());
+        assert!(!req.matches("0.5.0").unwrap());
+
+        let req = VersionRequirement::parse_requirement("~1.4.2").unwrap();
+        assert!(req.matches("1.4.7").unwrap());
+        assert!(!req.matches("1.5.0").unwrap());
+        assert!(!req.matches("1.4.1").unwrap());
+
+        let req = VersionRequirement::parse_requirement("1.*").unwrap();
+        assert!(req.matches("1.0.0").unwrap());
+        assert!(req.matches("1.99").unwrap());
+        assert!(!req.matches("2.0").unwrap());
+
+        let req = VersionRequirement::parse_requirement("!=1.3.0").unwrap();
+        assert!(req.matches("1.3.1").unwrap());
+        assert!(!req.matches("1.3").unwrap());
+    }
+
+    #[test]
+    fn test_prerelease_and_distro_versions() {
+        let req = VersionRequirement::parse_requirement(">=3.28").unwrap();
+        assert!(req.matches("3.28.1-1").unwrap());
+        assert!(req.matches("1:3.20").unwrap());
+
+        let req = VersionRequirement::parse_requirement("==3.28.1").unwrap();
+        assert!(req.matches("3.28.1-1").unwrap());
+        assert!(req.matches("3.28.1-2").unwrap());
+
+        let req = VersionRequirement::parse_requirement(">=3.28.1-2").unwrap();
+        assert!(!req.matches("3.28.1-1").unwrap());
+        assert!(req.matches("3.28.1-10").unwrap());
+
+        let req = VersionRequirement::parse_requirement(">=1.0").unwrap();
+        assert!(!req.matches("2.0.0-rc.1").unwrap());
+
+        let req = VersionRequirement::parse_requirement(">=2.0.0-rc.1").unwrap();
+        assert!(req.matches("2.0.0-rc.2").unwrap());
+        assert!(req.matches("2.0.0").unwrap());
+        assert!(!req.matches("2.0.0-beta.1").unwrap());
+    }
+
+    #[test]
+    fn test_invalid_requirements() {
+        assert_eq!(
+            VersionRequirement::parse_requirement(""),
+            Err(VersionError::EmptyRequirement)
+        );
+        assert!(matches!(
+            VersionRequirement::parse_requirement(">=1.2, banana"),
+            Err(VersionError::InvalidRequirement { .. })
+        ));
+        assert!(VersionRequirement::parse_requirement("1.*.3").is_err());
+    }
+
+    #[test]
+    fn test_version_ordering() {
+        assert!(is_newer("1.10.0", "1.9.0").unwrap());
+        assert!(is_newer("2.0.0", "2.0.0-rc.1").unwrap());
+        assert!(is_newer("3.28.1-2", "3.28.1-1").unwrap());
+        assert!(is_newer("1:1.0", "2.0").unwrap());
+        assert!(!is_newer("1.2", "1.2.0").unwrap());
     }
 }
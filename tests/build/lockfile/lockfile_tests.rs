@@ -0,0 +1,89 @@
+mod tests {
+    use reponere::build::lockfile::lockfile::{
+        LockedPackage, LockedSource, LockedSystemDependency, Lockfile, LockfileError,
+    };
+    use reponere::build::package::package::Source;
+    use tempfile::TempDir;
+
+    fn locked_package() -> LockedPackage {
+        LockedPackage {
+            name: "ripgrep".to_string(),
+            version: "15.1.0".to_string(),
+            recipe_sha256: "ab".repeat(32),
+            source: LockedSource::Git {
+                repo: "https://github.com/BurntSushi/ripgrep.git".to_string(),
+                commit: "0123456789abcdef0123456789abcdef01234567".to_string(),
+            },
+            dependencies: vec![],
+            system_dependencies: vec![LockedSystemDependency {
+                name: "cargo".to_string(),
+                version: Some("1.80.0-1".to_string()),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("reponere.lock");
+
+        let lockfile = Lockfile {
+            packages: vec![locked_package()],
+            ..Lockfile::default()
+        };
+        lockfile.save(&path).unwrap();
+
+        let loaded = Lockfile::load(&path).unwrap();
+        assert_eq!(loaded, lockfile);
+        assert!(loaded.get_package("ripgrep").is_some());
+    }
+
+    #[test]
+    fn test_load_rejects_unknown_version() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("reponere.lock");
+        std::fs::write(&path, "version = 99\n").unwrap();
+
+        assert!(matches!(
+            Lockfile::load(&path),
+            Err(LockfileError::UnsupportedVersion(99))
+        ));
+    }
+
+    #[test]
+    fn test_pin_git_source_to_commit() {
+        let source = Source::Git {
+            repo: "https://github.com/BurntSushi/ripgrep.git".to_string(),
+            tag: Some("15.1.0".to_string()),
+            branch: None,
+            commit: None,
+            expected_commit: None,
+            verify_signature: Some(true),
+            signing_keys: Some(vec!["0123456789ABCDEF".to_string()]),
+            submodules: None,
+            lfs: None,
+        };
+
+        let locked = locked_package().source;
+        assert!(locked.matches(&source));
+
+        match locked.pin(&source) {
+            Source::Git {
+                tag,
+                commit,
+                verify_signature,
+                signing_keys,
+                ..
+            } => {
+                assert_eq!(tag, None);
+                assert_eq!(
+                    commit.as_deref(),
+                    Some("0123456789abcdef0123456789abcdef01234567")
+                );
+                assert_eq!(verify_signature, Some(true));
+                assert_eq!(signing_keys, Some(vec!["0123456789ABCDEF".to_string()]));
+            }
+            other => panic!("expected git source, got {other:?}"),
+        }
+    }
+}
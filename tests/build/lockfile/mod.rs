@@ -0,0 +1 @@
+pub mod lockfile_tests;
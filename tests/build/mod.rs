@@ -1,6 +1,10 @@
 pub mod build_step_handler;
 pub mod dependency_handler;
+pub mod lockfile;
 pub mod package;
 pub mod package_manager;
 pub mod package_tracker;
+pub mod patch;
+pub mod registry;
 pub mod source;
+pub mod staging;
@@ -1,5 +1,8 @@
 mod tests {
-    use reponere::build::{package::package::Source, package::parse::PackageParser};
+    use reponere::build::{
+        package::package::{Build, BuildHelper, BuildPhase, Source, Submodules},
+        package::parse::PackageParser,
+    };
 
     #[test]
     pub fn test_parse() {
@@ -8,13 +11,22 @@ mod tests {
         assert_eq!(package.name, "mypackage");
         assert_eq!(package.version, "1.0.0");
         assert_eq!(package.description, Some("package description".to_string()));
+        assert_eq!(package.license, Some("MIT OR Apache-2.0".to_string()));
+        assert_eq!(package.maintainers, vec!["Jane Doe <jane@example.com>"]);
+        assert_eq!(package.keywords, vec!["example", "cli"]);
+        assert_eq!(package.provides, vec!["mypkg"]);
         assert_eq!(
             package.source,
             Source::Git {
                 repo: "https://github.com/user/mypackage.git".to_string(),
                 tag: Some("v1.0.0".to_string()),
                 branch: None,
-                commit: None
+                commit: None,
+                expected_commit: None,
+                verify_signature: None,
+                signing_keys: None,
+                submodules: None,
+                lfs: None,
             }
         );
         assert_eq!(package.dependencies.runtime.len(), 2);
@@ -42,5 +54,85 @@ mod tests {
         );
         assert_eq!(package.build.as_ref().unwrap().steps[1], "make -j$(nproc)");
         assert_eq!(package.build.as_ref().unwrap().steps[2], "make install");
+        assert_eq!(package.build.as_ref().unwrap().env["CFLAGS"], "-O2");
+    }
+
+    #[test]
+    pub fn test_parse_submodules() {
+        let parse = |value: &str| {
+            serde_yml::from_str::<Source>(&format!(
+                "!Git\nrepo: \"https://example.com/repo.git\"\nsubmodules: {value}\n"
+            ))
+        };
+
+        let submodules = |source: Source| match source {
+            Source::Git { submodules, .. } => submodules,
+            _ => panic!("Expected git source"),
+        };
+
+        assert_eq!(
+            submodules(parse("true").unwrap()),
+            Some(Submodules::Enabled)
+        );
+        assert_eq!(
+            submodules(parse("false").unwrap()),
+            Some(Submodules::Disabled)
+        );
+        assert_eq!(
+            submodules(parse("recursive").unwrap()),
+            Some(Submodules::Recursive)
+        );
+        assert!(parse("sometimes").is_err());
+    }
+
+    #[test]
+    pub fn test_parse_build_phases() {
+        let build: Build = serde_yml::from_str(
+            "helper: cmake\nargs: [\"-DFOO=ON\"]\nprepare:\n  - \"./bootstrap\"\n\
+             check: []\nsteps:\n  - \"echo done\"\n",
+        )
+        .unwrap();
+        assert_eq!(build.helper, Some(BuildHelper::Cmake));
+
+        let phases = build.phases();
+        let commands = |phase: BuildPhase| {
+            phases
+                .iter()
+                .find(|(p, _)| *p == phase)
+                .map(|(_, commands)| commands.clone())
+                .unwrap()
+        };
+
+        assert_eq!(commands(BuildPhase::Prepare), ["./bootstrap"]);
+        assert_eq!(
+            commands(BuildPhase::Configure),
+            [
+                "cmake -B build -DCMAKE_BUILD_TYPE=Release -DCMAKE_INSTALL_PREFIX=\"$PREFIX\" -DFOO=ON"
+            ]
+        );
+        assert_eq!(
+            commands(BuildPhase::Build),
+            ["cmake --build build -j \"$JOBS\"", "echo done"]
+        );
+        assert!(commands(BuildPhase::Check).is_empty());
+        assert_eq!(
+            commands(BuildPhase::Install),
+            ["DESTDIR=\"$DESTDIR\" cmake --install build"]
+        );
+    }
+
+    #[test]
+    pub fn test_parse_build_helpers() {
+        let helper = |name: &str| serde_yml::from_str::<BuildHelper>(name);
+
+        assert_eq!(helper("cargo").unwrap(), BuildHelper::Cargo);
+        assert_eq!(helper("python-pip").unwrap(), BuildHelper::PythonPip);
+        assert!(helper("scons").is_err());
+        assert!(
+            BuildHelper::Cargo
+                .commands(BuildPhase::Install, &[])
+                .iter()
+                .all(|c| c.contains("--root \"$DESTDIR$PREFIX\""))
+        );
     }
 }
@@ -1,12 +1,16 @@
 mod tests {
-    use reponere::build::package::package::{Dependency, InstalledPackage};
+    use reponere::build::package::package::{
+        Dependency, InstalledBuild, InstalledPackage, Manifest,
+    };
     use reponere::build::package_tracker::package_tracker::PackageTracker;
+    use reponere::handlers::install_handler::{FileConflict, find_conflicts};
     use tempfile::NamedTempFile;
 
     fn dummy_dependency() -> Dependency {
         Dependency {
             name: "serde".to_string(),
             version_req: Some(">=1.0".to_string()),
+            source: None,
         }
     }
 
@@ -16,6 +20,7 @@ mod tests {
             version: "1.0.0".to_string(),
             install_path: "/usr/local".to_string(),
             dependencies: vec![dummy_dependency()],
+            manifest: Manifest::default(),
         }
     }
 
@@ -68,4 +73,86 @@ mod tests {
         assert!(tracker.get_package("pkg1").is_some());
         assert!(tracker.get_package("pkg2").is_some());
     }
+
+    fn package_with_files(name: &str, files: &[&str]) -> InstalledPackage {
+        InstalledPackage {
+            manifest: Manifest {
+                files: files.iter().map(|f| f.to_string()).collect(),
+                directories: vec![],
+            },
+            ..dummy_package(name)
+        }
+    }
+
+    #[test]
+    fn test_owner_of() {
+        let mut tracker = PackageTracker::default();
+        tracker.add_package(package_with_files("pkg1", &["/opt/bin/one"]));
+        tracker.add_package(package_with_files("pkg2", &["/opt/bin/two"]));
+
+        assert_eq!(tracker.owner_of("/opt/bin/two").unwrap().name, "pkg2");
+        assert!(tracker.owner_of("/opt/bin/three").is_none());
+    }
+
+    #[test]
+    fn test_find_conflicts() {
+        let mut tracker = PackageTracker::default();
+        tracker.add_package(package_with_files("pkg1", &["/opt/bin/shim"]));
+        tracker.add_package(package_with_files("pkg2", &["/opt/bin/two"]));
+
+        let files = vec![
+            "/opt/bin/shim".to_string(),
+            "/opt/bin/two".to_string(),
+            "/nonexistent/reponere/new".to_string(),
+        ];
+        let conflicts = find_conflicts("pkg2", &files, &tracker);
+
+        assert_eq!(
+            conflicts,
+            vec![FileConflict {
+                path: "/opt/bin/shim".to_string(),
+                owner: Some("pkg1".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_take_ownership() {
+        let mut tracker = PackageTracker::default();
+        tracker.add_package(package_with_files(
+            "pkg1",
+            &["/opt/bin/shim", "/opt/bin/one"],
+        ));
+        tracker.add_package(package_with_files("pkg2", &["/opt/bin/shim"]));
+
+        tracker.take_ownership("pkg2", &["/opt/bin/shim".to_string()]);
+
+        assert_eq!(tracker.owner_of("/opt/bin/shim").unwrap().name, "pkg2");
+        assert_eq!(
+            tracker.get_package("pkg1").unwrap().manifest.files,
+            vec!["/opt/bin/one".to_string()]
+        );
+    }
+
+    fn build(version: &str) -> InstalledBuild {
+        InstalledBuild {
+            version: version.to_string(),
+            install_path: String::new(),
+            dependencies: vec![],
+            archive: format!("/history/mypkg/{version}.tar.gz"),
+        }
+    }
+
+    #[test]
+    fn test_record_build_keeps_latest_builds() {
+        let mut tracker = PackageTracker::default();
+
+        assert!(tracker.record_build("mypkg", build("1.0.0"), 2).is_empty());
+        assert!(tracker.record_build("mypkg", build("2.0.0"), 2).is_empty());
+        assert!(tracker.record_build("mypkg", build("1.0.0"), 2).is_empty());
+
+        let dropped = tracker.record_build("mypkg", build("3.0.0"), 2);
+        assert_eq!(dropped, vec![build("2.0.0")]);
+        assert_eq!(tracker.history("mypkg"), &[build("1.0.0"), build("3.0.0")]);
+    }
 }
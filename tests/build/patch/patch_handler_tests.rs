@@ -0,0 +1,111 @@
+mod tests {
+    use std::fs;
+
+    use reponere::build::package::package::Patch;
+    use reponere::build::patch::patch_handler::{PatchHandler, PatchHandlerError};
+    use tempfile::TempDir;
+
+    const TELEMETRY_PATCH: &str = "\
+diff --git a/config.h b/config.h
+--- a/config.h
++++ b/config.h
+@@ -1,2 +1,2 @@
+ #define NAME \"mypkg\"
+-#define TELEMETRY 1
++#define TELEMETRY 0
+";
+
+    fn make_source() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("config.h"),
+            "#define NAME \"mypkg\"\n#define TELEMETRY 1\n",
+        )
+        .unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_apply_inline_patch() {
+        let source = make_source();
+        let handler = PatchHandler::new(
+            vec![Patch::Inline {
+                diff: TELEMETRY_PATCH.to_string(),
+            }],
+            ".",
+        );
+
+        handler.apply_patches(source.path(), &mut |_| {}).unwrap();
+
+        let content = fs::read_to_string(source.path().join("config.h")).unwrap();
+        assert!(content.contains("#define TELEMETRY 0"));
+        assert!(!source.path().join(".git").exists());
+    }
+
+    #[test]
+    fn test_apply_file_patch_relative_to_recipe() {
+        let source = make_source();
+        let recipe_dir = TempDir::new().unwrap();
+        fs::create_dir_all(recipe_dir.path().join("patches")).unwrap();
+        fs::write(
+            recipe_dir.path().join("patches/telemetry.patch"),
+            TELEMETRY_PATCH,
+        )
+        .unwrap();
+
+        let handler = PatchHandler::new(
+            vec![Patch::File {
+                path: "patches/telemetry.patch".to_string(),
+            }],
+            recipe_dir.path(),
+        );
+
+        handler.apply_patches(source.path(), &mut |_| {}).unwrap();
+
+        let content = fs::read_to_string(source.path().join("config.h")).unwrap();
+        assert!(content.contains("#define TELEMETRY 0"));
+    }
+
+    #[test]
+    fn test_failed_hunk_names_patch() {
+        let source = make_source();
+        let handler = PatchHandler::new(
+            vec![
+                Patch::Inline {
+                    diff: TELEMETRY_PATCH.to_string(),
+                },
+                Patch::Inline {
+                    diff: TELEMETRY_PATCH.to_string(),
+                },
+            ],
+            ".",
+        );
+
+        let err = handler
+            .apply_patches(source.path(), &mut |_| {})
+            .unwrap_err();
+        match err {
+            PatchHandlerError::FailedApplyingPatch { name, .. } => {
+                assert_eq!(name, "inline patch #2")
+            }
+            _ => panic!("Expected FailedApplyingPatch error"),
+        }
+        assert!(!source.path().join(".git").exists());
+    }
+
+    #[test]
+    fn test_missing_patch_file() {
+        let source = make_source();
+        let handler = PatchHandler::new(
+            vec![Patch::File {
+                path: "missing.patch".to_string(),
+            }],
+            ".",
+        );
+
+        assert!(matches!(
+            handler.apply_patches(source.path(), &mut |_| {}),
+            Err(PatchHandlerError::FailedReadingPatch { .. })
+        ));
+    }
+}
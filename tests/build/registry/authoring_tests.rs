@@ -0,0 +1,53 @@
+mod tests {
+    use reponere::build::registry::authoring::{BuildSystem, find_version_tag, is_spdx_expression};
+
+    #[test]
+    fn test_spdx_expressions() {
+        for valid in [
+            "MIT",
+            "MIT OR Apache-2.0",
+            "GPL-2.0-or-later WITH Classpath-exception-2.0",
+            "(MIT OR Apache-2.0) AND BSD-3-Clause",
+            "GPL-2.0+",
+            "LicenseRef-Proprietary",
+        ] {
+            assert!(is_spdx_expression(valid), "{valid}");
+        }
+
+        for invalid in [
+            "",
+            "MIT OR",
+            "MIT and Apache-2.0",
+            "(MIT",
+            "MIT)",
+            "GPL v2",
+            "MIT WITH",
+        ] {
+            assert!(!is_spdx_expression(invalid), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_detect_build_system_and_tag() {
+        assert_eq!(
+            BuildSystem::detect(&["Makefile", "CMakeLists.txt"]),
+            Some(BuildSystem::CMake)
+        );
+        assert_eq!(
+            BuildSystem::detect(&["configure", "Makefile"]),
+            Some(BuildSystem::Autotools)
+        );
+        assert_eq!(BuildSystem::detect(&["README.md"]), None);
+
+        let tags = ["1.0.0", "tool-2.0.0", "v3.0.0"];
+        assert_eq!(
+            find_version_tag(&tags, "tool", "2.0.0"),
+            Some("tool-2.0.0".to_string())
+        );
+        assert_eq!(
+            find_version_tag(&tags, "tool", "3.0.0"),
+            Some("v3.0.0".to_string())
+        );
+        assert_eq!(find_version_tag(&tags, "tool", "4.0.0"), None);
+    }
+}
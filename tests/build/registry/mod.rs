@@ -0,0 +1,3 @@
+pub mod authoring_tests;
+pub mod registry_tests;
+pub mod remote_tests;
@@ -0,0 +1,119 @@
+mod tests {
+    use std::{collections::BTreeMap, fs, path::Path};
+
+    use reponere::build::registry::registry_handler::{LOCAL_REGISTRY, Registry, RegistrySource};
+    use tempfile::TempDir;
+
+    use crate::common::write_release;
+
+    fn source(name: &str, path: &Path, priority: i32) -> RegistrySource {
+        RegistrySource {
+            name: name.to_string(),
+            path: path.to_path_buf(),
+            priority,
+        }
+    }
+
+    /// A local registry dir with the `internal` and `community` registries
+    /// mirrored below it, both providing `tool`.
+    fn layered(dir: &TempDir) -> Vec<RegistrySource> {
+        let local = dir.path();
+        write_release(local, "mine", "1.0.0");
+        write_release(&local.join("internal"), "tool", "1.0.0-internal");
+        write_release(&local.join("community"), "tool", "2.0.0");
+        write_release(&local.join("community"), "other", "1.0.0");
+
+        vec![
+            source(LOCAL_REGISTRY, local, 0),
+            source("internal", &local.join("internal"), 100),
+            source("community", &local.join("community"), 10),
+        ]
+    }
+
+    #[test]
+    fn test_higher_priority_registry_overrides_package() {
+        let dir = TempDir::new().unwrap();
+        let sources = layered(&dir);
+
+        let registry = Registry::resync_sources_and_save(
+            &dir.path().join("index.json"),
+            &sources,
+            &BTreeMap::new(),
+        );
+
+        let tool = registry.get_package("tool").unwrap();
+        assert_eq!(tool.registry, "internal");
+        assert_eq!(tool.shadowed, vec!["community".to_string()]);
+        assert_eq!(tool.latest, "1.0.0-internal");
+        assert!(!tool.releases.contains_key("2.0.0"));
+        assert_eq!(
+            registry.resolve_release("tool", None).unwrap().registry(),
+            "internal"
+        );
+
+        assert_eq!(registry.get_package("other").unwrap().registry, "community");
+        assert_eq!(
+            registry.get_package("mine").unwrap().registry,
+            LOCAL_REGISTRY
+        );
+        // mirrored registries are not also read as part of the local one
+        assert_eq!(registry.get_packages().len(), 3);
+    }
+
+    #[test]
+    fn test_pinned_package_uses_pinned_registry() {
+        let dir = TempDir::new().unwrap();
+        let sources = layered(&dir);
+        let pins = BTreeMap::from([
+            ("tool".to_string(), "community".to_string()),
+            ("other".to_string(), "internal".to_string()),
+        ]);
+
+        let registry =
+            Registry::resync_sources_and_save(&dir.path().join("index.json"), &sources, &pins);
+
+        let tool = registry.get_package("tool").unwrap();
+        assert_eq!(tool.registry, "community");
+        assert_eq!(tool.latest, "2.0.0");
+        assert_eq!(tool.shadowed, vec!["internal".to_string()]);
+
+        // pinned to a registry that does not provide it
+        assert_eq!(registry.get_package("other").unwrap().registry, "community");
+    }
+
+    #[test]
+    fn test_origin_survives_index_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let sources = layered(&dir);
+        let index = dir.path().join("index.json");
+
+        Registry::resync_sources_and_save(&index, &sources, &BTreeMap::new());
+        let loaded = Registry::load_or_sync_sources(&index, &[], &BTreeMap::new());
+
+        assert_eq!(loaded.get_package("tool").unwrap().registry, "internal");
+    }
+
+    #[test]
+    fn test_index_stores_latest_metadata() {
+        let dir = TempDir::new().unwrap();
+        write_release(dir.path(), "tool", "1.0.0");
+        let release = dir.path().join("tool/1.1.0");
+        fs::create_dir_all(&release).unwrap();
+        fs::write(
+            release.join("package_build.yaml"),
+            "name: tool\nversion: \"1.1.0\"\ndescription: \"a tool\"\nlicense: \"MIT\"\n\
+             keywords: [\"cli\"]\nsource:\n  !Local\n  path: \".\"\n\
+             dependencies:\n  runtime:\n  build:\n",
+        )
+        .unwrap();
+
+        let index = dir.path().join("index.json");
+        Registry::resync_from_directory_and_save(&index, dir.path());
+        let loaded = Registry::load_or_sync(&index, dir.path());
+
+        let metadata = &loaded.get_package("tool").unwrap().metadata;
+        assert_eq!(metadata.description.as_deref(), Some("a tool"));
+        assert_eq!(metadata.license.as_deref(), Some("MIT"));
+        assert_eq!(metadata.keywords, vec!["cli"]);
+    }
+}
@@ -0,0 +1,313 @@
+mod tests {
+    use std::{
+        fs,
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        path::{Path, PathBuf},
+        process::Command,
+        thread,
+    };
+
+    use git2::{Repository, Signature};
+    use reponere::build::registry::registry_handler::Registry;
+    use reponere::build::registry::remote::{RemoteError, RemoteRegistry, RemoteSource};
+    use reponere::build::registry::signature::SignatureError;
+    use reponere::handlers::registry_handler;
+    use tempfile::TempDir;
+
+    use crate::common::{recipe, write_release};
+
+    fn commit_all(repo: &Repository, message: &str) {
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        index.update_all(["*"], None).unwrap();
+        index.write().unwrap();
+
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("test", "test@example.com").unwrap();
+        let parent = repo.head().ok().map(|h| h.peel_to_commit().unwrap());
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parent.iter().collect::<Vec<_>>(),
+        )
+        .unwrap();
+    }
+
+    /// Serves files below `root` until the test process exits.
+    fn serve(root: PathBuf) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request = String::new();
+                BufReader::new(&stream).read_line(&mut request).unwrap();
+
+                let path = request.split_whitespace().nth(1).unwrap_or("/");
+                let response = match fs::read(root.join(path.trim_start_matches('/'))) {
+                    Ok(body) => {
+                        let mut response = format!(
+                            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                            body.len()
+                        )
+                        .into_bytes();
+                        response.extend(body);
+                        response
+                    }
+                    Err(_) => {
+                        b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                            .to_vec()
+                    }
+                };
+                stream.write_all(&response).unwrap();
+            }
+        });
+
+        format!("http://{address}")
+    }
+
+    /// Generates an ed25519 key pair, returning the private key path and
+    /// the public key line.
+    fn keygen(dir: &Path, name: &str) -> (PathBuf, String) {
+        let key = dir.join(name);
+        let status = Command::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-f"])
+            .arg(&key)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let public = fs::read_to_string(key.with_extension("pub")).unwrap();
+        (key, public)
+    }
+
+    fn signed_git_remote(url: &Path) -> RemoteRegistry {
+        RemoteRegistry {
+            name: "internal".to_string(),
+            priority: 0,
+            allow_unsigned: false,
+            source: RemoteSource::Git {
+                url: url.to_str().unwrap().to_string(),
+                branch: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_sync_git_remote() {
+        let upstream = TempDir::new().unwrap();
+        let repo = Repository::init(upstream.path()).unwrap();
+        write_release(upstream.path(), "hello", "1.0.0");
+        commit_all(&repo, "add hello");
+
+        let registry_dir = TempDir::new().unwrap();
+        let remote = RemoteRegistry {
+            name: "main".to_string(),
+            priority: 0,
+            allow_unsigned: true,
+            source: RemoteSource::Git {
+                url: upstream.path().to_str().unwrap().to_string(),
+                branch: None,
+            },
+        };
+
+        let local = remote.sync(registry_dir.path(), &[]).unwrap();
+        assert!(local.join("hello/1.0.0/package_build.yaml").exists());
+
+        // releases added and removed upstream show up on the next sync
+        fs::remove_dir_all(upstream.path().join("hello/1.0.0")).unwrap();
+        write_release(upstream.path(), "hello", "2.0.0");
+        commit_all(&repo, "replace hello");
+
+        remote.sync(registry_dir.path(), &[]).unwrap();
+        assert!(!local.join("hello/1.0.0").exists());
+
+        let registry = Registry::resync_from_directory_and_save(
+            &registry_dir.path().join("index.json"),
+            registry_dir.path(),
+        );
+        assert_eq!(registry.get_package("hello").unwrap().latest, "2.0.0");
+    }
+
+    #[test]
+    fn test_sync_http_remote() {
+        let served = TempDir::new().unwrap();
+        write_release(served.path(), "hello", "1.0.0");
+        fs::write(served.path().join("hello/1.0.0/fix.patch"), "patch").unwrap();
+        fs::write(
+            served.path().join("index.json"),
+            r#"{"packages": {"hello": {"1.0.0": {"files": ["fix.patch"]}}}}"#,
+        )
+        .unwrap();
+        let url = serve(served.path().to_path_buf());
+
+        let registry_dir = TempDir::new().unwrap();
+        let remote = RemoteRegistry {
+            name: "web".to_string(),
+            priority: 0,
+            allow_unsigned: true,
+            source: RemoteSource::Http { url },
+        };
+
+        let local = remote.sync(registry_dir.path(), &[]).unwrap();
+        assert_eq!(
+            fs::read_to_string(local.join("hello/1.0.0/package_build.yaml")).unwrap(),
+            recipe("hello", "1.0.0", &[])
+        );
+        assert!(local.join("hello/1.0.0/fix.patch").exists());
+    }
+
+    #[test]
+    fn test_sync_http_rejects_escaping_paths() {
+        let served = TempDir::new().unwrap();
+        fs::write(
+            served.path().join("index.json"),
+            r#"{"packages": {"..": {"1.0.0": {}}}}"#,
+        )
+        .unwrap();
+        let url = serve(served.path().to_path_buf());
+
+        let registry_dir = TempDir::new().unwrap();
+        let remote = RemoteRegistry {
+            name: "web".to_string(),
+            priority: 0,
+            allow_unsigned: true,
+            source: RemoteSource::Http { url },
+        };
+
+        assert!(matches!(
+            remote.sync(registry_dir.path(), &[]),
+            Err(RemoteError::InvalidPath(_))
+        ));
+    }
+
+    #[test]
+    fn test_sync_signed_git_remote_rejects_tampering() {
+        let keys = TempDir::new().unwrap();
+        let (key, public) = keygen(keys.path(), "maintainer");
+        let trusted = vec![public];
+
+        let upstream = TempDir::new().unwrap();
+        let repo = Repository::init(upstream.path()).unwrap();
+        write_release(upstream.path(), "hello", "1.0.0");
+        registry_handler::sign(upstream.path(), &key, &mut |_| {}).unwrap();
+        commit_all(&repo, "signed release");
+
+        let registry_dir = TempDir::new().unwrap();
+        let remote = signed_git_remote(upstream.path());
+        let local = remote.sync(registry_dir.path(), &trusted).unwrap();
+        assert!(local.join("hello/1.0.0/package_build.yaml").exists());
+
+        // a recipe changed without re-signing the index
+        fs::write(
+            upstream.path().join("hello/1.0.0/package_build.yaml"),
+            "name: evil\n",
+        )
+        .unwrap();
+        commit_all(&repo, "tampered");
+
+        assert!(matches!(
+            remote.sync(registry_dir.path(), &trusted),
+            Err(RemoteError::SignatureError(SignatureError::Tampered { .. }))
+        ));
+        assert_eq!(
+            fs::read_to_string(local.join("hello/1.0.0/package_build.yaml")).unwrap(),
+            recipe("hello", "1.0.0", &[])
+        );
+    }
+
+    #[test]
+    fn test_sync_git_remote_rejects_unsigned_content_by_default() {
+        let keys = TempDir::new().unwrap();
+        let (_, public) = keygen(keys.path(), "maintainer");
+
+        let upstream = TempDir::new().unwrap();
+        let repo = Repository::init(upstream.path()).unwrap();
+        write_release(upstream.path(), "hello", "1.0.0");
+        commit_all(&repo, "unsigned release");
+
+        let remote: RemoteRegistry = toml::from_str(&format!(
+            "name = \"internal\"\ntype = \"git\"\nurl = \"{}\"",
+            upstream.path().display()
+        ))
+        .unwrap();
+        assert!(!remote.allow_unsigned);
+
+        let registry_dir = TempDir::new().unwrap();
+        assert!(matches!(
+            remote.sync(registry_dir.path(), &[public]),
+            Err(RemoteError::SignatureError(SignatureError::Unsigned(_)))
+        ));
+    }
+
+    #[test]
+    fn test_sync_signed_http_remote_requires_trusted_key() {
+        let keys = TempDir::new().unwrap();
+        let (key, public) = keygen(keys.path(), "maintainer");
+        let (_, stranger) = keygen(keys.path(), "stranger");
+
+        let served = TempDir::new().unwrap();
+        write_release(served.path(), "hello", "1.0.0");
+        fs::write(served.path().join("hello/1.0.0/fix.patch"), "patch").unwrap();
+        registry_handler::sign(served.path(), &key, &mut |_| {}).unwrap();
+        let url = serve(served.path().to_path_buf());
+
+        let registry_dir = TempDir::new().unwrap();
+        let remote = RemoteRegistry {
+            name: "web".to_string(),
+            priority: 0,
+            allow_unsigned: false,
+            source: RemoteSource::Http { url },
+        };
+
+        assert!(matches!(
+            remote.sync(registry_dir.path(), &[stranger]),
+            Err(RemoteError::SignatureError(
+                SignatureError::VerificationFailed(_)
+            ))
+        ));
+
+        let local = remote.sync(registry_dir.path(), &[public]).unwrap();
+        assert!(local.join("hello/1.0.0/fix.patch").exists());
+    }
+
+    #[test]
+    fn test_parse_remotes_from_config() {
+        #[derive(serde::Deserialize)]
+        struct Remotes {
+            remotes: Vec<RemoteRegistry>,
+        }
+
+        let remotes: Remotes = toml::from_str(
+            r#"
+            [[remotes]]
+            name = "main"
+            type = "git"
+            url = "https://example.com/registry.git"
+            branch = "stable"
+
+            [[remotes]]
+            name = "web"
+            type = "http"
+            url = "https://example.com/registry"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            remotes.remotes[0].source,
+            RemoteSource::Git {
+                url: "https://example.com/registry.git".to_string(),
+                branch: Some("stable".to_string()),
+            }
+        );
+        assert_eq!(remotes.remotes[1].url(), "https://example.com/registry");
+    }
+}
@@ -0,0 +1,256 @@
+mod tests {
+    use std::{fs, io::Write, path::Path};
+
+    use reponere::build::package::package::Source;
+    use reponere::build::source::archive_handler::{
+        ArchiveFormat, ArchiveSource, ArchiveSourceHandler, ArchiveSourceHandlerError,
+    };
+    use sha2::{Digest, Sha256};
+    use tempfile::TempDir;
+
+    fn write_tar_gz(path: &Path) {
+        let file = fs::File::create(path).unwrap();
+        let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+
+        let data = b"hello";
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o755);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "mypkg-1.0.0/bin/hello", &data[..])
+            .unwrap();
+
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    enum Entry<'a> {
+        File(&'a str),
+        Symlink(&'a str, &'a str),
+        Hardlink(&'a str, &'a str),
+    }
+
+    fn write_tar_gz_entries(path: &Path, entries: &[Entry]) {
+        let file = fs::File::create(path).unwrap();
+        let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+
+        for entry in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_mode(0o644);
+            match entry {
+                Entry::File(name) => {
+                    header.set_size(5);
+                    header.set_cksum();
+                    builder
+                        .append_data(&mut header, name, &b"hello"[..])
+                        .unwrap();
+                }
+                Entry::Symlink(name, target) | Entry::Hardlink(name, target) => {
+                    header.set_entry_type(match entry {
+                        Entry::Symlink(..) => tar::EntryType::Symlink,
+                        _ => tar::EntryType::Link,
+                    });
+                    header.set_size(0);
+                    builder.append_link(&mut header, name, target).unwrap();
+                }
+            }
+        }
+
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    fn fetch_tar_gz(entries: &[Entry]) -> Result<TempDir, ArchiveSourceHandlerError> {
+        let dir = TempDir::new().unwrap();
+        let archive = dir.path().join("mypkg-1.0.0.tar.gz");
+        write_tar_gz_entries(&archive, entries);
+
+        let source = archive_source(format!("file://{}", archive.display()), None, Some(1));
+        ArchiveSourceHandler::new(ArchiveSource::from_source(&source).unwrap()).fetch()
+    }
+
+    fn write_zip(path: &Path) {
+        let file = fs::File::create(path).unwrap();
+        let mut writer = zip::ZipWriter::new(file);
+        writer
+            .start_file::<_, ()>("mypkg-1.0.0/README", Default::default())
+            .unwrap();
+        writer.write_all(b"readme").unwrap();
+        writer.finish().unwrap();
+    }
+
+    fn archive_source(url: String, sha256: Option<String>, strip: Option<usize>) -> Source {
+        Source::Archive {
+            url,
+            sha256,
+            blake3: None,
+            strip_components: strip,
+        }
+    }
+
+    #[test]
+    fn test_format_from_url() {
+        assert_eq!(
+            ArchiveFormat::from_url("https://example.com/pkg-1.0.tar.gz"),
+            Some(ArchiveFormat::TarGz)
+        );
+        assert_eq!(
+            ArchiveFormat::from_url("https://example.com/pkg-1.0.tar.xz"),
+            Some(ArchiveFormat::TarXz)
+        );
+        assert_eq!(
+            ArchiveFormat::from_url("https://example.com/pkg-1.0.tar.zst?raw=1"),
+            Some(ArchiveFormat::TarZst)
+        );
+        assert_eq!(
+            ArchiveFormat::from_url("file:///tmp/pkg.zip"),
+            Some(ArchiveFormat::Zip)
+        );
+        assert_eq!(ArchiveFormat::from_url("https://example.com/pkg.rar"), None);
+    }
+
+    #[test]
+    fn test_from_source_unsupported_format() {
+        let source = archive_source("https://example.com/pkg.rar".into(), None, None);
+
+        let err = ArchiveSource::from_source(&source).unwrap_err();
+        assert!(matches!(
+            err,
+            ArchiveSourceHandlerError::UnsupportedFormat(_)
+        ));
+    }
+
+    #[test]
+    fn test_fetch_tar_gz_with_strip_components() {
+        let dir = TempDir::new().unwrap();
+        let archive = dir.path().join("mypkg-1.0.0.tar.gz");
+        write_tar_gz(&archive);
+
+        let source = archive_source(format!("file://{}", archive.display()), None, Some(1));
+        let handler = ArchiveSourceHandler::new(ArchiveSource::from_source(&source).unwrap());
+
+        let tmp_dir = handler.fetch().unwrap();
+        let content = fs::read_to_string(tmp_dir.path().join("bin/hello")).unwrap();
+        assert_eq!(content, "hello");
+    }
+
+    #[test]
+    fn test_fetch_zip_without_strip_components() {
+        let dir = TempDir::new().unwrap();
+        let archive = dir.path().join("mypkg-1.0.0.zip");
+        write_zip(&archive);
+
+        let source = archive_source(format!("file://{}", archive.display()), None, None);
+        let handler = ArchiveSourceHandler::new(ArchiveSource::from_source(&source).unwrap());
+
+        let tmp_dir = handler.fetch().unwrap();
+        assert!(tmp_dir.path().join("mypkg-1.0.0/README").exists());
+    }
+
+    #[test]
+    fn test_fetch_verifies_checksum() {
+        let dir = TempDir::new().unwrap();
+        let archive = dir.path().join("mypkg-1.0.0.tar.gz");
+        write_tar_gz(&archive);
+        let sha256 = hex::encode(Sha256::digest(fs::read(&archive).unwrap()));
+
+        let source = archive_source(format!("file://{}", archive.display()), Some(sha256), None);
+        let handler = ArchiveSourceHandler::new(ArchiveSource::from_source(&source).unwrap());
+        assert!(handler.fetch().is_ok());
+
+        let source = archive_source(
+            format!("file://{}", archive.display()),
+            Some("0".repeat(64)),
+            None,
+        );
+        let handler = ArchiveSourceHandler::new(ArchiveSource::from_source(&source).unwrap());
+        assert!(matches!(
+            handler.fetch(),
+            Err(ArchiveSourceHandlerError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_fetch_verifies_blake3() {
+        let dir = TempDir::new().unwrap();
+        let archive = dir.path().join("mypkg-1.0.0.tar.gz");
+        write_tar_gz(&archive);
+        let hash = blake3::hash(&fs::read(&archive).unwrap())
+            .to_hex()
+            .to_string();
+
+        let source = Source::Archive {
+            url: format!("file://{}", archive.display()),
+            sha256: None,
+            blake3: Some(hash),
+            strip_components: None,
+        };
+        let handler = ArchiveSourceHandler::new(ArchiveSource::from_source(&source).unwrap());
+        assert!(handler.fetch().is_ok());
+
+        let source = Source::Archive {
+            url: format!("file://{}", archive.display()),
+            sha256: None,
+            blake3: Some("f".repeat(64)),
+            strip_components: None,
+        };
+        let handler = ArchiveSourceHandler::new(ArchiveSource::from_source(&source).unwrap());
+        assert!(matches!(
+            handler.fetch(),
+            Err(ArchiveSourceHandlerError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_fetch_tar_rejects_escaping_symlinks() {
+        for target in ["/etc", "../../outside", "lib/../../.."] {
+            let result = fetch_tar_gz(&[Entry::Symlink("mypkg-1.0.0/escape", target)]);
+            assert!(
+                matches!(
+                    result,
+                    Err(ArchiveSourceHandlerError::FailedExtractingError(_))
+                ),
+                "{target} was accepted"
+            );
+        }
+    }
+
+    #[test]
+    fn test_fetch_tar_rejects_writes_through_symlinks() {
+        let outside = std::env::temp_dir().join("reponere-archive-escape");
+        let _ = fs::remove_file(&outside);
+
+        // each link stays inside on its own, together `up` is the parent of the tree
+        let result = fetch_tar_gz(&[
+            Entry::Symlink("mypkg-1.0.0/here", "."),
+            Entry::Symlink("mypkg-1.0.0/up", "here/.."),
+            Entry::File("mypkg-1.0.0/up/reponere-archive-escape"),
+        ]);
+
+        assert!(matches!(
+            result,
+            Err(ArchiveSourceHandlerError::FailedExtractingError(_))
+        ));
+        assert!(!outside.exists());
+    }
+
+    #[test]
+    fn test_fetch_tar_strips_hardlink_targets() {
+        let tmp_dir = fetch_tar_gz(&[
+            Entry::File("mypkg-1.0.0/bin/hello"),
+            Entry::Hardlink("mypkg-1.0.0/bin/hi", "mypkg-1.0.0/bin/hello"),
+        ])
+        .unwrap();
+        assert_eq!(
+            fs::read_to_string(tmp_dir.path().join("bin/hi")).unwrap(),
+            "hello"
+        );
+
+        let result = fetch_tar_gz(&[Entry::Hardlink("mypkg-1.0.0/passwd", "/etc/passwd")]);
+        assert!(matches!(
+            result,
+            Err(ArchiveSourceHandlerError::FailedExtractingError(_))
+        ));
+    }
+}
@@ -0,0 +1,101 @@
+mod tests {
+    use std::{fs, path::Path};
+
+    use reponere::build::package::package::Source;
+    use reponere::build::source::local_handler::{
+        LocalSource, LocalSourceHandler, LocalSourceHandlerError,
+    };
+    use tempfile::TempDir;
+
+    fn local_source(path: &str, ignore: Option<Vec<&str>>) -> Source {
+        Source::Local {
+            path: path.to_string(),
+            ignore: ignore.map(|i| i.into_iter().map(String::from).collect()),
+        }
+    }
+
+    fn make_checkout(root: &Path) {
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("target/debug")).unwrap();
+        fs::write(root.join("Cargo.toml"), "[package]").unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(root.join("src/notes.log"), "scratch").unwrap();
+        fs::write(root.join("target/debug/app"), "binary").unwrap();
+    }
+
+    #[test]
+    fn test_fetch_copies_directory() {
+        let checkout = TempDir::new().unwrap();
+        make_checkout(checkout.path());
+
+        let source = local_source(checkout.path().to_str().unwrap(), None);
+        let handler = LocalSourceHandler::new(LocalSource::from_source(&source).unwrap(), ".");
+
+        let tmp_dir = handler.fetch().unwrap();
+        assert!(tmp_dir.path().join("Cargo.toml").exists());
+        assert!(tmp_dir.path().join("src/main.rs").exists());
+        assert!(tmp_dir.path().join("target/debug/app").exists());
+    }
+
+    #[test]
+    fn test_fetch_honors_ignore_list() {
+        let checkout = TempDir::new().unwrap();
+        make_checkout(checkout.path());
+
+        let source = local_source(
+            checkout.path().to_str().unwrap(),
+            Some(vec!["target", "*.log"]),
+        );
+        let handler = LocalSourceHandler::new(LocalSource::from_source(&source).unwrap(), ".");
+
+        let tmp_dir = handler.fetch().unwrap();
+        assert!(tmp_dir.path().join("src/main.rs").exists());
+        assert!(!tmp_dir.path().join("src/notes.log").exists());
+        assert!(!tmp_dir.path().join("target").exists());
+    }
+
+    #[test]
+    fn test_blake3_tracks_copied_contents() {
+        let checkout = TempDir::new().unwrap();
+        make_checkout(checkout.path());
+
+        let source = local_source(
+            checkout.path().to_str().unwrap(),
+            Some(vec!["target", "*.log"]),
+        );
+        let handler = LocalSourceHandler::new(LocalSource::from_source(&source).unwrap(), ".");
+        let digest = handler.blake3().unwrap();
+
+        // ignored files are not part of the digest
+        fs::write(checkout.path().join("src/notes.log"), "more scratch").unwrap();
+        assert_eq!(handler.blake3().unwrap(), digest);
+
+        fs::write(checkout.path().join("src/main.rs"), "fn main() { }").unwrap();
+        assert_ne!(handler.blake3().unwrap(), digest);
+    }
+
+    #[test]
+    fn test_relative_path_resolves_against_base_dir() {
+        let base = TempDir::new().unwrap();
+        make_checkout(&base.path().join("checkout"));
+
+        let source = local_source("checkout", None);
+        let handler =
+            LocalSourceHandler::new(LocalSource::from_source(&source).unwrap(), base.path());
+
+        assert_eq!(handler.resolve_path(), base.path().join("checkout"));
+        let tmp_dir = handler.fetch().unwrap();
+        assert!(tmp_dir.path().join("Cargo.toml").exists());
+    }
+
+    #[test]
+    fn test_fetch_missing_directory() {
+        let source = local_source("/nonexistent/reponere/checkout", None);
+        let handler = LocalSourceHandler::new(LocalSource::from_source(&source).unwrap(), ".");
+
+        assert!(matches!(
+            handler.fetch(),
+            Err(LocalSourceHandlerError::NotADirectory(_))
+        ));
+    }
+}
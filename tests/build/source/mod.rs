@@ -1 +1,3 @@
+pub mod archive_handler_tests;
+pub mod local_handler_tests;
 pub mod source_handler_tests;
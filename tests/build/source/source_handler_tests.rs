@@ -1,8 +1,14 @@
 mod tests {
-    use reponere::build::package::package::Source;
+    use std::{fs, path::Path};
+
+    use git2::{Repository, Signature};
+    use reponere::build::package::package::{Source, Submodules};
     use reponere::build::source::source_handler::{
-        GitSource, GitSourceHandler, GitSourceHandlerError,
+        GitSource, GitSourceHandler, GitSourceHandlerError, gpg_trusted_signer,
     };
+    use tempfile::TempDir;
+
+    use crate::common::init_tagged_repo;
 
     #[test]
     fn test_from_source_valid() {
@@ -11,6 +17,11 @@ mod tests {
             tag: Some("v1.0.0".into()),
             branch: None,
             commit: None,
+            expected_commit: None,
+            verify_signature: None,
+            signing_keys: None,
+            submodules: None,
+            lfs: None,
         };
 
         let git_source = GitSource::from_source(&source).unwrap();
@@ -27,6 +38,11 @@ mod tests {
             tag: Some("v1.0.0".into()),
             branch: Some("main".into()),
             commit: None,
+            expected_commit: None,
+            verify_signature: None,
+            signing_keys: None,
+            submodules: None,
+            lfs: None,
         };
 
         let err = GitSource::from_source(&source).unwrap_err();
@@ -46,6 +62,11 @@ mod tests {
             tag: None,
             branch: Some("master".into()),
             commit: None,
+            expected_commit: None,
+            verify_signature: None,
+            signing_keys: None,
+            submodules: None,
+            lfs: None,
         };
 
         let git_source = GitSource::from_source(&source).unwrap();
@@ -67,6 +88,11 @@ mod tests {
             tag: None,
             branch: None,
             commit: None,
+            expected_commit: None,
+            verify_signature: None,
+            signing_keys: None,
+            submodules: None,
+            lfs: None,
         };
 
         let git_source = GitSource::from_source(&source).unwrap();
@@ -78,4 +104,296 @@ mod tests {
         assert!(path.exists());
         assert!(path.join(".git").exists());
     }
+
+    fn tagged_source(repo: &Path, expected_commit: Option<String>, verify: bool) -> Source {
+        Source::Git {
+            repo: repo.to_str().unwrap().into(),
+            tag: Some("v1.0.0".into()),
+            branch: None,
+            commit: None,
+            expected_commit,
+            verify_signature: Some(verify),
+            signing_keys: None,
+            submodules: None,
+            lfs: None,
+        }
+    }
+
+    #[test]
+    fn test_fetch_tag_matching_expected_commit() {
+        let repo_dir = TempDir::new().unwrap();
+        let oid = init_tagged_repo(repo_dir.path());
+
+        let source = tagged_source(repo_dir.path(), Some(oid), false);
+        let handler = GitSourceHandler::new(GitSource::from_source(&source).unwrap());
+
+        let tmp_dir = handler.fetch().unwrap();
+        assert!(tmp_dir.path().join("README").exists());
+    }
+
+    #[test]
+    fn test_fetch_tag_with_moved_commit_fails() {
+        let repo_dir = TempDir::new().unwrap();
+        init_tagged_repo(repo_dir.path());
+
+        let source = tagged_source(repo_dir.path(), Some("0".repeat(40)), false);
+        let handler = GitSourceHandler::new(GitSource::from_source(&source).unwrap());
+
+        assert!(matches!(
+            handler.fetch(),
+            Err(GitSourceHandlerError::CommitMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_expected_commit_requires_tag() {
+        let source = Source::Git {
+            repo: "https://github.com/user/repo.git".into(),
+            tag: None,
+            branch: Some("main".into()),
+            commit: None,
+            expected_commit: Some("0".repeat(40)),
+            verify_signature: None,
+            signing_keys: None,
+            submodules: None,
+            lfs: None,
+        };
+
+        assert!(matches!(
+            GitSource::from_source(&source),
+            Err(GitSourceHandlerError::InvalidSpecifications(_))
+        ));
+    }
+
+    #[test]
+    fn test_fetch_unsigned_tag_fails_verification() {
+        let repo_dir = TempDir::new().unwrap();
+        init_tagged_repo(repo_dir.path());
+
+        let source = tagged_source(repo_dir.path(), None, true);
+        let handler = GitSourceHandler::new(GitSource::from_source(&source).unwrap());
+
+        assert!(matches!(
+            handler.fetch(),
+            Err(GitSourceHandlerError::MissingSignature(_))
+        ));
+    }
+
+    #[test]
+    fn test_gpg_signer_must_be_listed_or_trusted() {
+        let subkey = "AAAA1111AAAA1111AAAA1111AAAA1111AAAA1111";
+        let primary = "BBBB2222BBBB2222BBBB2222BBBB2222BBBB2222";
+        let good = format!(
+            "[GNUPG:] NEWSIG\n[GNUPG:] GOODSIG 2222BBBB2222BBBB Upstream\n\
+             [GNUPG:] VALIDSIG {subkey} 2026-01-01 1767225600 0 4 0 22 10 00 {primary}\n\
+             [GNUPG:] TRUST_UNDEFINED 0 pgp\n"
+        );
+
+        // any key in the keyring used to pass
+        assert_eq!(gpg_trusted_signer(&good, &[]), None);
+        assert_eq!(
+            gpg_trusted_signer(&good, &["cccc3333cccc3333cccc3333cccc3333cccc3333".into()]),
+            None
+        );
+        assert_eq!(
+            gpg_trusted_signer(&good, &[subkey.to_lowercase()]),
+            Some(subkey.to_string())
+        );
+        assert_eq!(
+            gpg_trusted_signer(
+                &good,
+                &["BBBB 2222 BBBB 2222 BBBB  2222 BBBB 2222 BBBB 2222".into()]
+            ),
+            Some(subkey.to_string())
+        );
+
+        let trusted = good.replace("TRUST_UNDEFINED", "TRUST_FULLY");
+        assert_eq!(gpg_trusted_signer(&trusted, &[]), Some(subkey.to_string()));
+        assert_eq!(
+            gpg_trusted_signer(
+                &trusted,
+                &["cccc3333cccc3333cccc3333cccc3333cccc3333".into()]
+            ),
+            None
+        );
+
+        let bad = "[GNUPG:] NEWSIG\n[GNUPG:] BADSIG 2222BBBB2222BBBB Upstream\n\
+                   [GNUPG:] TRUST_ULTIMATE 0 pgp\n";
+        assert_eq!(gpg_trusted_signer(bad, &[primary.into()]), None);
+    }
+
+    fn commit_file(repo: &Repository, name: &str, content: &str) -> git2::Oid {
+        let workdir = repo.workdir().unwrap();
+        fs::write(workdir.join(name), content).unwrap();
+
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(name)).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Reponere", "reponere@example.com").unwrap();
+        let parents = match repo.head() {
+            Ok(head) => vec![head.peel_to_commit().unwrap()],
+            Err(_) => vec![],
+        };
+        let parents: Vec<&git2::Commit> = parents.iter().collect();
+
+        repo.commit(Some("HEAD"), &signature, &signature, name, &tree, &parents)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_mirror_path_is_keyed_by_url() {
+        let mirror = |repo| GitSourceHandler::mirror_path(Path::new("/cache"), repo);
+
+        let path = mirror("https://github.com/BurntSushi/ripgrep.git");
+        assert_eq!(path.parent().unwrap(), Path::new("/cache/git"));
+        assert_eq!(path, mirror("https://github.com/BurntSushi/ripgrep.git/"));
+
+        // URLs that only differ in characters a sanitized name would replace
+        assert_ne!(
+            mirror("https://example.com/a/b.git"),
+            mirror("https://example.com/a_b.git")
+        );
+    }
+
+    #[test]
+    fn test_cached_fetch_works_offline() {
+        let origin = TempDir::new().unwrap();
+        let cache = TempDir::new().unwrap();
+        init_tagged_repo(origin.path());
+
+        let source = tagged_source(origin.path(), None, false);
+        let handler = GitSourceHandler::new(GitSource::from_source(&source).unwrap())
+            .with_cache(cache.path());
+
+        let tmp_dir = handler.fetch().unwrap();
+        assert!(tmp_dir.path().join("README").exists());
+        assert!(tmp_dir.path().join(".git").exists());
+
+        let mirror = GitSourceHandler::mirror_path(cache.path(), origin.path().to_str().unwrap());
+        assert!(mirror.exists());
+
+        drop(origin);
+        let tmp_dir = handler.fetch().unwrap();
+        assert_eq!(
+            fs::read_to_string(tmp_dir.path().join("README")).unwrap(),
+            "hello"
+        );
+    }
+
+    #[test]
+    fn test_cached_fetch_updates_branch_incrementally() {
+        let origin = TempDir::new().unwrap();
+        let cache = TempDir::new().unwrap();
+        let repo = Repository::init(origin.path()).unwrap();
+        commit_file(&repo, "first", "1");
+        let branch = repo.head().unwrap().shorthand().unwrap().to_string();
+
+        let source = Source::Git {
+            repo: origin.path().to_str().unwrap().into(),
+            tag: None,
+            branch: Some(branch),
+            commit: None,
+            expected_commit: None,
+            verify_signature: None,
+            signing_keys: None,
+            submodules: None,
+            lfs: None,
+        };
+        let handler = GitSourceHandler::new(GitSource::from_source(&source).unwrap())
+            .with_cache(cache.path());
+
+        let tmp_dir = handler.fetch().unwrap();
+        assert!(tmp_dir.path().join("first").exists());
+        assert!(!tmp_dir.path().join("second").exists());
+
+        commit_file(&repo, "second", "2");
+        let tmp_dir = handler.fetch().unwrap();
+        assert!(tmp_dir.path().join("second").exists());
+    }
+
+    #[test]
+    fn test_cached_fetch_default_head() {
+        let origin = TempDir::new().unwrap();
+        let cache = TempDir::new().unwrap();
+        let repo = Repository::init(origin.path()).unwrap();
+        commit_file(&repo, "first", "1");
+
+        let source = Source::Git {
+            repo: origin.path().to_str().unwrap().into(),
+            tag: None,
+            branch: None,
+            commit: None,
+            expected_commit: None,
+            verify_signature: None,
+            signing_keys: None,
+            submodules: None,
+            lfs: None,
+        };
+        let handler = GitSourceHandler::new(GitSource::from_source(&source).unwrap())
+            .with_cache(cache.path());
+
+        let tmp_dir = handler.fetch().unwrap();
+        assert!(tmp_dir.path().join("first").exists());
+    }
+
+    fn init_repo_with_submodule(dir: &Path, submodule_origin: &Path) {
+        let repo = Repository::init(dir).unwrap();
+        let mut submodule = repo
+            .submodule(
+                submodule_origin.to_str().unwrap(),
+                Path::new("vendor/lib"),
+                true,
+            )
+            .unwrap();
+        submodule.clone(None).unwrap();
+        submodule.add_finalize().unwrap();
+
+        let mut index = repo.index().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Reponere", "reponere@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "vendor", &tree, &[])
+            .unwrap();
+    }
+
+    fn submodule_source(repo: &Path, submodules: Option<Submodules>) -> Source {
+        Source::Git {
+            repo: repo.to_str().unwrap().into(),
+            tag: None,
+            branch: None,
+            commit: None,
+            expected_commit: None,
+            verify_signature: None,
+            signing_keys: None,
+            submodules,
+            lfs: None,
+        }
+    }
+
+    #[test]
+    fn test_fetch_initializes_submodules() {
+        let library = TempDir::new().unwrap();
+        let origin = TempDir::new().unwrap();
+        let cache = TempDir::new().unwrap();
+        commit_file(
+            &Repository::init(library.path()).unwrap(),
+            "lib.c",
+            "int x;",
+        );
+        init_repo_with_submodule(origin.path(), library.path());
+
+        let source = submodule_source(origin.path(), None);
+        let handler = GitSourceHandler::new(GitSource::from_source(&source).unwrap());
+        let tmp_dir = handler.fetch().unwrap();
+        assert!(!tmp_dir.path().join("vendor/lib/lib.c").exists());
+
+        let source = submodule_source(origin.path(), Some(Submodules::Enabled));
+        let handler = GitSourceHandler::new(GitSource::from_source(&source).unwrap());
+        let tmp_dir = handler.fetch().unwrap();
+        assert!(tmp_dir.path().join("vendor/lib/lib.c").exists());
+
+        let handler = GitSourceHandler::new(GitSource::from_source(&source).unwrap())
+            .with_cache(cache.path());
+        let tmp_dir = handler.fetch().unwrap();
+        assert!(tmp_dir.path().join("vendor/lib/lib.c").exists());
+    }
 }
@@ -0,0 +1 @@
+pub mod staging_handler_tests;
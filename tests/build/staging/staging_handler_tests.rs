@@ -0,0 +1,114 @@
+mod tests {
+    use std::fs;
+
+    use reponere::build::staging::staging_handler::{StagingHandler, remove_manifest_files};
+    use reponere::handlers::events::event::Event;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_install_records_manifest() {
+        let root = TempDir::new().unwrap();
+        fs::create_dir_all(root.path().join("bin")).unwrap();
+
+        let staging = StagingHandler::new(root.path()).unwrap();
+        fs::create_dir_all(staging.path().join("bin")).unwrap();
+        fs::create_dir_all(staging.path().join("share/mypkg")).unwrap();
+        fs::write(staging.path().join("bin/mypkg"), "binary").unwrap();
+        fs::write(staging.path().join("share/mypkg/README"), "readme").unwrap();
+
+        let mut installed = Vec::new();
+        let manifest = staging
+            .install(&mut |event| installed.push(event.message()))
+            .unwrap();
+
+        let path = |p: &str| root.path().join(p).to_string_lossy().to_string();
+        assert_eq!(
+            manifest.files,
+            vec![path("bin/mypkg"), path("share/mypkg/README")]
+        );
+        assert_eq!(
+            manifest.directories,
+            vec![path("share"), path("share/mypkg")]
+        );
+        assert_eq!(installed.len(), 2);
+        assert_eq!(
+            fs::read_to_string(root.path().join("bin/mypkg")).unwrap(),
+            "binary"
+        );
+    }
+
+    #[test]
+    fn test_failed_install_leaves_root_untouched() {
+        let root = TempDir::new().unwrap();
+        fs::create_dir_all(root.path().join("bin")).unwrap();
+        fs::write(root.path().join("bin/mypkg"), "old").unwrap();
+        // a file where the package expects a directory makes the copy fail
+        fs::write(root.path().join("usr"), "not a directory").unwrap();
+
+        let staging = StagingHandler::new(root.path()).unwrap();
+        fs::create_dir_all(staging.path().join("bin")).unwrap();
+        fs::create_dir_all(staging.path().join("usr")).unwrap();
+        fs::create_dir_all(staging.path().join("share/mypkg")).unwrap();
+        fs::write(staging.path().join("bin/mypkg"), "new").unwrap();
+        fs::write(staging.path().join("usr/libmypkg.so"), "library").unwrap();
+        fs::write(staging.path().join("share/mypkg/README"), "readme").unwrap();
+
+        assert!(staging.install(&mut |_| {}).is_err());
+
+        assert_eq!(
+            fs::read_to_string(root.path().join("bin/mypkg")).unwrap(),
+            "old"
+        );
+        let mut left: Vec<_> = fs::read_dir(root.path().join("bin"))
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        left.sort();
+        assert_eq!(left, ["mypkg"]);
+        assert!(!root.path().join("share").exists());
+    }
+
+    #[test]
+    fn test_remove_manifest_files_keeps_foreign_files() {
+        let root = TempDir::new().unwrap();
+        fs::create_dir_all(root.path().join("bin")).unwrap();
+        fs::write(root.path().join("bin/other"), "not ours").unwrap();
+
+        let staging = StagingHandler::new(root.path()).unwrap();
+        fs::create_dir_all(staging.path().join("bin")).unwrap();
+        fs::create_dir_all(staging.path().join("share/mypkg")).unwrap();
+        fs::write(staging.path().join("bin/mypkg"), "binary").unwrap();
+        fs::write(staging.path().join("share/mypkg/README"), "readme").unwrap();
+
+        let manifest = staging.install(&mut |_| {}).unwrap();
+        remove_manifest_files(&manifest).unwrap();
+
+        assert!(!root.path().join("bin/mypkg").exists());
+        assert!(!root.path().join("share").exists());
+        assert!(root.path().join("bin/other").exists());
+    }
+
+    #[test]
+    fn test_archive_roundtrip() {
+        let root = TempDir::new().unwrap();
+        let history = TempDir::new().unwrap();
+        let archive = history.path().join("mypkg/1.0.0.tar.gz");
+
+        let staging = StagingHandler::new(root.path()).unwrap();
+        fs::create_dir_all(staging.path().join("bin")).unwrap();
+        fs::write(staging.path().join("bin/mypkg"), "binary").unwrap();
+        staging.archive(&archive).unwrap();
+
+        let restored = StagingHandler::from_archive(root.path(), &archive).unwrap();
+        assert_eq!(
+            restored.staged_files().unwrap(),
+            staging.staged_files().unwrap()
+        );
+
+        restored.install(&mut |_| {}).unwrap();
+        assert_eq!(
+            fs::read_to_string(root.path().join("bin/mypkg")).unwrap(),
+            "binary"
+        );
+    }
+}
@@ -0,0 +1,55 @@
+//! Fixtures shared by the integration tests.
+
+use std::{fs, path::Path};
+
+use git2::{Repository, Signature};
+use reponere::build::registry::registry_handler::Registry;
+
+/// Creates a repository at `dir` with one commit tagged `v1.0.0` and
+/// returns the commit id.
+pub fn init_tagged_repo(dir: &Path) -> String {
+    let repo = Repository::init(dir).unwrap();
+    fs::write(dir.join("README"), "hello").unwrap();
+
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("README")).unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let signature = Signature::now("Reponere", "reponere@example.com").unwrap();
+    let oid = repo
+        .commit(Some("HEAD"), &signature, &signature, "initial", &tree, &[])
+        .unwrap();
+
+    let commit = repo.find_object(oid, None).unwrap();
+    repo.tag("v1.0.0", &commit, &signature, "release", false)
+        .unwrap();
+    oid.to_string()
+}
+
+/// A minimal recipe with a local source. Each of `runtime` is a flow
+/// mapping such as `{ name: lib, version_req: ">=1.0" }`.
+pub fn recipe(name: &str, version: &str, runtime: &[&str]) -> String {
+    let runtime: String = runtime.iter().map(|dep| format!("\n    - {dep}")).collect();
+    format!(
+        "name: {name}\nversion: \"{version}\"\nsource:\n  !Local\n  path: \".\"\n\
+         dependencies:\n  runtime:{runtime}\n  build:\n"
+    )
+}
+
+pub fn write_recipe(root: &Path, name: &str, version: &str, recipe: &str) {
+    let release = root.join(name).join(version);
+    fs::create_dir_all(&release).unwrap();
+    fs::write(release.join("package_build.yaml"), recipe).unwrap();
+}
+
+pub fn write_package(root: &Path, name: &str, version: &str, runtime: &[&str]) {
+    write_recipe(root, name, version, &recipe(name, version, runtime));
+}
+
+pub fn write_release(root: &Path, name: &str, version: &str) {
+    write_package(root, name, version, &[]);
+}
+
+/// Indexes the registry dir `root` into `root/index.json`.
+pub fn registry(root: &Path) -> Registry {
+    Registry::resync_from_directory_and_save(&root.join("index.json"), root)
+}
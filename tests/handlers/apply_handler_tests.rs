@@ -0,0 +1,167 @@
+mod tests {
+    use std::fs;
+
+    use reponere::build::desired_state::desired_state::{DesiredState, DesiredStateError};
+    use reponere::build::package::package::{
+        Dependency, DependencySource, InstalledPackage, Manifest,
+    };
+    use reponere::build::package_tracker::package_tracker::PackageTracker;
+    use reponere::build::registry::registry_handler::Registry;
+    use reponere::handlers::apply_handler::{self, ApplyAction};
+    use tempfile::TempDir;
+
+    use crate::common::{self, write_release};
+
+    fn registry(dir: &TempDir, releases: &[(&str, &str)]) -> Registry {
+        for (name, version) in releases {
+            write_release(dir.path(), name, version);
+        }
+        common::registry(dir.path())
+    }
+
+    fn installed(name: &str, version: &str) -> InstalledPackage {
+        InstalledPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            install_path: String::new(),
+            dependencies: vec![],
+            manifest: Manifest::default(),
+        }
+    }
+
+    fn desired(packages: &[(&str, &str)]) -> DesiredState {
+        DesiredState {
+            packages: packages
+                .iter()
+                .map(|(name, req)| (name.to_string(), req.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_plan_reconciles_versions() {
+        let dir = TempDir::new().unwrap();
+        let reg = registry(
+            &dir,
+            &[
+                ("new", "1.0.0"),
+                ("up", "1.0.0"),
+                ("up", "2.0.0"),
+                ("down", "1.0.0"),
+                ("down", "2.0.0"),
+                ("same", "1.0.0"),
+            ],
+        );
+
+        let mut tracker = PackageTracker::default();
+        tracker.add_package(installed("up", "1.0.0"));
+        tracker.add_package(installed("down", "2.0.0"));
+        tracker.add_package(installed("same", "1.0.0"));
+        tracker.add_package(installed("extra", "1.0.0"));
+
+        let state = desired(&[
+            ("new", "*"),
+            ("up", "2.0.0"),
+            ("down", "<2.0.0"),
+            ("same", "*"),
+        ]);
+        let plan = apply_handler::plan(&reg, &tracker, &state, false).unwrap();
+
+        assert_eq!(plan.len(), 3);
+        assert!(plan.contains(&ApplyAction::Install {
+            name: "new".to_string(),
+            version: "1.0.0".to_string(),
+        }));
+        assert!(plan.contains(&ApplyAction::Upgrade {
+            name: "up".to_string(),
+            from: "1.0.0".to_string(),
+            to: "2.0.0".to_string(),
+        }));
+        assert!(plan.contains(&ApplyAction::Downgrade {
+            name: "down".to_string(),
+            from: "2.0.0".to_string(),
+            to: "1.0.0".to_string(),
+        }));
+    }
+
+    #[test]
+    fn test_plan_prunes_unlisted_packages() {
+        let dir = TempDir::new().unwrap();
+        let reg = registry(&dir, &[("kept", "1.0.0")]);
+
+        let mut tracker = PackageTracker::default();
+        tracker.add_package(installed("kept", "1.0.0"));
+        tracker.add_package(installed("extra", "1.0.0"));
+
+        let state = desired(&[("kept", "1.0.0")]);
+        assert!(
+            apply_handler::plan(&reg, &tracker, &state, false)
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            apply_handler::plan(&reg, &tracker, &state, true).unwrap(),
+            vec![ApplyAction::Remove {
+                name: "extra".to_string(),
+                version: "1.0.0".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_plan_ignores_constraints_of_pruned_packages() {
+        let dir = TempDir::new().unwrap();
+        let reg = registry(&dir, &[("lib", "1.0.0"), ("lib", "2.0.0")]);
+
+        let mut tracker = PackageTracker::default();
+        tracker.add_package(installed("lib", "1.0.0"));
+        tracker.add_package(InstalledPackage {
+            dependencies: vec![Dependency {
+                name: "lib".to_string(),
+                version_req: Some("==1.0.0".to_string()),
+                source: Some(DependencySource::Reponere),
+            }],
+            ..installed("old", "1.0.0")
+        });
+
+        let state = desired(&[("lib", "2.0.0")]);
+        assert!(apply_handler::plan(&reg, &tracker, &state, false).is_err());
+        assert_eq!(
+            apply_handler::plan(&reg, &tracker, &state, true).unwrap(),
+            vec![
+                ApplyAction::Remove {
+                    name: "old".to_string(),
+                    version: "1.0.0".to_string(),
+                },
+                ApplyAction::Upgrade {
+                    name: "lib".to_string(),
+                    from: "1.0.0".to_string(),
+                    to: "2.0.0".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_desired_state() {
+        let dir = TempDir::new().unwrap();
+
+        let toml = dir.path().join("toolchain.toml");
+        fs::write(&toml, "[packages]\nnvim = \"^0.10\"\nripgrep = \"*\"\n").unwrap();
+        let state = DesiredState::load(&toml).unwrap();
+        assert_eq!(state.packages["nvim"], "^0.10");
+        assert_eq!(state.packages.len(), 2);
+
+        let yaml = dir.path().join("toolchain.yaml");
+        fs::write(&yaml, "packages:\n  nvim: \">=0.9, <0.11\"\n").unwrap();
+        let state = DesiredState::load(&yaml).unwrap();
+        assert_eq!(state.packages["nvim"], ">=0.9, <0.11");
+
+        let invalid = dir.path().join("invalid.toml");
+        fs::write(&invalid, "[packages]\nnvim = \">=\"\n").unwrap();
+        assert!(matches!(
+            DesiredState::load(&invalid),
+            Err(DesiredStateError::InvalidRequirement { .. })
+        ));
+    }
+}
@@ -0,0 +1,151 @@
+mod tests {
+    use std::fs;
+
+    use reponere::build::lockfile::lockfile::{LockedSource, Lockfile};
+    use reponere::build::package_manager::manager::PackageManagerApi;
+    use reponere::build::package_tracker::package_tracker::PackageTracker;
+    use reponere::build::registry::registry_handler::Registry;
+    use reponere::handlers::install_handler::{self, InstallError};
+    use reponere::handlers::lock_handler;
+    use reponere::util::config::Config;
+    use tempfile::TempDir;
+
+    use crate::build::dependency_handler::dependency_handler_tests::MockPackageManager;
+    use crate::common::{init_tagged_repo, write_package};
+
+    #[test]
+    fn test_lock_records_commit_and_system_versions() {
+        let dir = TempDir::new().unwrap();
+        let upstream = dir.path().join("upstream");
+        let commit = init_tagged_repo(&upstream);
+
+        let config = Config::for_root(dir.path());
+        let release = config.registry_path.join("tool/1.0.0");
+        fs::create_dir_all(&release).unwrap();
+        fs::write(
+            release.join("package_build.yaml"),
+            format!(
+                "name: tool\nversion: \"1.0.0\"\nsource:\n  !Git\n  repo: \"{}\"\n  tag: v1.0.0\n\
+                 dependencies:\n  runtime:\n    - name: python3\n  build:\n",
+                upstream.display()
+            ),
+        )
+        .unwrap();
+        let reg =
+            Registry::resync_from_directory_and_save(&config.index_path, &config.registry_path);
+
+        let package_manager = MockPackageManager::new().with_installed("python3", "3.12.1-1");
+        let lockfile = lock_handler::run(
+            &config,
+            &reg,
+            &PackageTracker::default(),
+            &package_manager,
+            &["tool".to_string()],
+            &mut |_| {},
+        )
+        .unwrap();
+
+        let locked = lockfile.get_package("tool").unwrap();
+        assert_eq!(locked.version, "1.0.0");
+        assert_eq!(
+            locked.source,
+            LockedSource::Git {
+                repo: upstream.to_str().unwrap().to_string(),
+                commit,
+            }
+        );
+        assert_eq!(locked.system_dependencies[0].name, "python3");
+        assert_eq!(
+            locked.system_dependencies[0].version.as_deref(),
+            Some("3.12.1-1")
+        );
+    }
+
+    #[test]
+    fn test_lock_records_local_source_digest() {
+        let dir = TempDir::new().unwrap();
+        let config = Config::for_root(dir.path());
+        write_package(&config.registry_path, "tool", "1.0.0", &[]);
+        let release = config.registry_path.join("tool/1.0.0");
+        fs::write(release.join("main.c"), "int main() {}").unwrap();
+        let reg =
+            Registry::resync_from_directory_and_save(&config.index_path, &config.registry_path);
+
+        let lock = || {
+            lock_handler::run(
+                &config,
+                &reg,
+                &PackageTracker::default(),
+                &MockPackageManager::new(),
+                &["tool".to_string()],
+                &mut |_| {},
+            )
+            .unwrap()
+            .get_package("tool")
+            .unwrap()
+            .source
+            .clone()
+        };
+
+        let locked = lock();
+        assert!(matches!(&locked, LockedSource::Local { path, .. } if path == "."));
+
+        fs::write(release.join("main.c"), "int main() { return 1; }").unwrap();
+        assert_ne!(lock(), locked);
+    }
+
+    #[test]
+    fn test_locked_system_dependencies_verify_after_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let config = Config::for_root(dir.path());
+        let release = config.registry_path.join("tool/1.0.0");
+        fs::create_dir_all(&release).unwrap();
+        fs::write(
+            release.join("package_build.yaml"),
+            "name: tool\nversion: \"1.0.0\"\nsource:\n  !Local\n  path: \".\"\n\
+             dependencies:\n  runtime:\n    - name: python3\n  build:\n    - name: gcc\n",
+        )
+        .unwrap();
+        let reg =
+            Registry::resync_from_directory_and_save(&config.index_path, &config.registry_path);
+
+        let package_manager = MockPackageManager::new()
+            .with_installed("python3", "3.12.1-1")
+            .with_available("gcc", "14.2.0-1");
+        let lockfile_path = dir.path().join("reponere.lock");
+        lock_handler::run(
+            &config,
+            &reg,
+            &PackageTracker::default(),
+            &package_manager,
+            &["tool".to_string()],
+            &mut |_| {},
+        )
+        .unwrap()
+        .save(&lockfile_path)
+        .unwrap();
+
+        let lockfile = Lockfile::load(&lockfile_path).unwrap();
+        let locked = lockfile.get_package("tool").unwrap();
+        let gcc = locked
+            .system_dependencies
+            .iter()
+            .find(|d| d.name == "gcc")
+            .unwrap();
+        assert_eq!(gcc.version.as_deref(), Some("14.2.0-1"));
+
+        // a build dependency is installed for the build and removed afterwards
+        install_handler::verify_locked_system_dependencies(locked, &package_manager).unwrap();
+        package_manager.install("gcc").unwrap();
+        package_manager.uninstall("gcc").unwrap();
+        install_handler::verify_locked_system_dependencies(locked, &package_manager).unwrap();
+
+        let upgraded = MockPackageManager::new()
+            .with_installed("python3", "3.13.0-1")
+            .with_available("gcc", "14.2.0-1");
+        assert!(matches!(
+            install_handler::verify_locked_system_dependencies(locked, &upgraded),
+            Err(InstallError::LockMismatch(_))
+        ));
+    }
+}
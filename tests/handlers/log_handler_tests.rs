@@ -0,0 +1,62 @@
+mod tests {
+    use std::{fs, path::Path};
+
+    use reponere::build::build_step_handler::build_log::BuildLogs;
+    use reponere::handlers::events::LogEvent;
+    use reponere::handlers::log_handler::{self, LogError};
+    use reponere::util::config::Config;
+    use tempfile::TempDir;
+
+    fn write_log(dir: &Path, name: &str, file: &str, contents: &str) {
+        fs::create_dir_all(dir.join(name)).unwrap();
+        fs::write(dir.join(name).join(file), contents).unwrap();
+    }
+
+    fn lines(config: &Config, name: &str, tail: Option<usize>) -> Vec<String> {
+        let mut lines = Vec::new();
+        log_handler::run(config, name, tail, &mut |event| {
+            if let LogEvent::Line { line } = event {
+                lines.push(line);
+            }
+        })
+        .unwrap();
+        lines
+    }
+
+    #[test]
+    fn test_shows_latest_log() {
+        let dir = TempDir::new().unwrap();
+        let config = Config::for_root(dir.path());
+        write_log(&config.log_path, "tool", "900-1.0.0.log", "old\n");
+        write_log(&config.log_path, "tool", "1000-1.1.0.log", "==> make\nok\n");
+        write_log(&config.log_path, "tool", "notes.txt", "ignored\n");
+
+        assert_eq!(lines(&config, "tool", None), ["==> make", "ok"]);
+        assert_eq!(lines(&config, "tool", Some(1)), ["ok"]);
+    }
+
+    #[test]
+    fn test_missing_log() {
+        let dir = TempDir::new().unwrap();
+        let config = Config::for_root(dir.path());
+
+        let result = log_handler::run(&config, "tool", None, &mut |_| {});
+        assert!(matches!(result, Err(LogError::NoLog(name)) if name == "tool"));
+    }
+
+    #[test]
+    fn test_prune_keeps_most_recent() {
+        let dir = TempDir::new().unwrap();
+        for started in [3, 1, 20, 2] {
+            write_log(dir.path(), "tool", &format!("{started}-1.0.0.log"), "");
+        }
+
+        let logs = BuildLogs::new(dir.path());
+        logs.prune("tool", 2).unwrap();
+
+        let kept = logs.logs("tool").unwrap();
+        assert_eq!(kept.len(), 2);
+        assert!(kept[0].ends_with("3-1.0.0.log"));
+        assert!(kept[1].ends_with("20-1.0.0.log"));
+    }
+}
@@ -0,0 +1,8 @@
+pub mod apply_handler_tests;
+pub mod lock_handler_tests;
+pub mod log_handler_tests;
+pub mod registry_handler_tests;
+pub mod rollback_handler_tests;
+pub mod search_handler_tests;
+pub mod sync_handler_tests;
+pub mod upgrade_handler_tests;
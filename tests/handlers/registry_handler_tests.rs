@@ -0,0 +1,193 @@
+mod tests {
+    use std::{fs, path::Path};
+
+    use git2::{Repository, Signature};
+    use reponere::build::package::package::BuildHelper;
+    use reponere::build::package::parse::PackageParser;
+    use reponere::handlers::events::RegistryEvent;
+    use reponere::handlers::events::event::Event;
+    use reponere::handlers::registry_handler::{self, RegistryError};
+    use tempfile::TempDir;
+
+    use crate::common::write_recipe;
+
+    fn upstream_with_tag(files: &[&str], tag: &str) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        for file in files {
+            fs::write(dir.path().join(file), "").unwrap();
+        }
+
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("test", "test@example.com").unwrap();
+        let commit = repo
+            .commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+        repo.tag_lightweight(tag, &repo.find_object(commit, None).unwrap(), false)
+            .unwrap();
+        dir
+    }
+
+    fn lint_messages(dir: &Path) -> Vec<String> {
+        let mut messages = Vec::new();
+        registry_handler::lint(dir, &mut |event| {
+            if let RegistryEvent::LintIssue { message, .. } = event {
+                messages.push(message);
+            }
+        })
+        .unwrap();
+        messages
+    }
+
+    #[test]
+    fn test_new_scaffolds_recipe_from_repo() {
+        let upstream = upstream_with_tag(&["Cargo.toml", "README.md"], "v1.2.0");
+        let registry = TempDir::new().unwrap();
+        let repo = upstream.path().to_str().unwrap();
+
+        let recipe =
+            registry_handler::new(registry.path(), "tool", "1.2.0", repo, &mut |_| {}).unwrap();
+        assert_eq!(
+            recipe,
+            registry.path().join("tool/1.2.0/package_build.yaml")
+        );
+
+        let package = PackageParser::new(recipe.to_str().unwrap())
+            .parse()
+            .unwrap();
+        assert_eq!(package.version, "1.2.0");
+        assert_eq!(package.dependencies.build[0].name, "cargo");
+        let build = package.build.unwrap();
+        assert_eq!(build.helper, Some(BuildHelper::Cargo));
+        assert!(build.steps.is_empty());
+        assert!(build.network);
+        assert!(package.install_path.is_none());
+        assert!(
+            fs::read_to_string(&recipe)
+                .unwrap()
+                .contains("tag: \"v1.2.0\"")
+        );
+
+        assert!(lint_messages(registry.path()).is_empty());
+        assert!(matches!(
+            registry_handler::new(registry.path(), "tool", "1.2.0", repo, &mut |_| {}),
+            Err(RegistryError::AlreadyExists(_))
+        ));
+    }
+
+    #[test]
+    fn test_lint_reports_invalid_recipes() {
+        let registry = TempDir::new().unwrap();
+        write_recipe(
+            registry.path(),
+            "both",
+            "1.0.0",
+            "name: both\nversion: \"1.0.0\"\nsource:\n  !Git\n  repo: \"r\"\n  tag: \"v1\"\n  \
+             branch: \"main\"\ndependencies:\n  runtime:\n  build:\ninstall_path: \"../bin/both\"\n",
+        );
+        write_recipe(
+            registry.path(),
+            "badreq",
+            "1.0.0",
+            "name: badreq\nversion: \"1.0.0\"\nsource:\n  !Local\n  path: \".\"\n\
+             dependencies:\n  runtime:\n    - name: x\n      version_req: \">=\"\n  build:\n",
+        );
+        write_recipe(
+            registry.path(),
+            "moved",
+            "2.0.0",
+            "name: moved\nversion: \"1.0.0\"\nsource:\n  !Local\n  path: \".\"\n\
+             dependencies:\n  runtime:\n  build:\nbuild:\n  steps:\n    - \"make install\"\n\
+             install_path: \".local/bin/moved\"\n",
+        );
+
+        let messages = lint_messages(registry.path());
+        assert_eq!(messages.len(), 5, "{messages:?}");
+        assert!(messages[0].starts_with("failed to parse"));
+        assert!(messages[1].contains("Only one of commit, tag, or branch"));
+        assert!(messages[2].contains("must not contain .."));
+        assert!(messages[3].contains("does not match its directory"));
+        assert!(messages[4].contains("installs outside $DESTDIR"));
+    }
+
+    #[test]
+    fn test_lint_counts_issues_and_allows_missing_install_path() {
+        let registry = TempDir::new().unwrap();
+        write_recipe(
+            registry.path(),
+            "plain",
+            "1.0.0",
+            "name: plain\nversion: \"1.0.0\"\nsource:\n  !Local\n  path: \".\"\n\
+             dependencies:\n  runtime:\n  build:\n",
+        );
+        assert_eq!(
+            registry_handler::lint(registry.path(), &mut |_| {}).unwrap(),
+            0
+        );
+
+        write_recipe(
+            registry.path(),
+            "plain",
+            "2.0.0",
+            "name: plain\nversion: \"1.0.0\"\nsource:\n  !Local\n  path: \".\"\n\
+             dependencies:\n  runtime:\n  build:\n",
+        );
+        assert_eq!(
+            registry_handler::lint(registry.path(), &mut |_| {}).unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_lint_accepts_bundled_registry() {
+        assert!(lint_messages(Path::new("resources/registry")).is_empty());
+    }
+
+    #[test]
+    fn test_bump_copies_latest_release_forward() {
+        let registry = TempDir::new().unwrap();
+        write_recipe(
+            registry.path(),
+            "tool",
+            "1.0.0",
+            "name: tool\nversion: \"1.0.0\"\n",
+        );
+        write_recipe(
+            registry.path(),
+            "tool",
+            "1.1.0",
+            "name: tool\nversion: \"1.1.0\"\n\nsource:\n  !Git\n  repo: \"https://example.com/tool.git\"\n  \
+             tag: \"v1.1.0\"\n  expected_commit: \"abcdef1\"\n\n# keep me\ndependencies:\n  runtime:\n  \
+             build:\n\ninstall_path: \".local/bin/tool\"\n",
+        );
+        fs::write(registry.path().join("tool/1.1.0/fix.patch"), "patch").unwrap();
+
+        let mut messages = Vec::new();
+        let recipe = registry_handler::bump(registry.path(), "tool", "1.2.0", &mut |event| {
+            messages.push(event.message())
+        })
+        .unwrap();
+
+        let text = fs::read_to_string(&recipe).unwrap();
+        assert!(text.contains("version: \"1.2.0\""));
+        assert!(text.contains("tag: \"v1.2.0\""));
+        assert!(text.contains("# keep me"));
+        assert!(!text.contains("expected_commit"));
+        assert!(registry.path().join("tool/1.2.0/fix.patch").exists());
+        assert!(
+            messages
+                .iter()
+                .any(|m| m.contains("removed expected_commit"))
+        );
+
+        assert!(matches!(
+            registry_handler::bump(registry.path(), "missing", "1.0.0", &mut |_| {}),
+            Err(RegistryError::PackageNotFound(_))
+        ));
+    }
+}
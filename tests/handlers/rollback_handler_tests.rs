@@ -0,0 +1,96 @@
+mod tests {
+    use reponere::build::package::package::{InstalledBuild, InstalledPackage, Manifest};
+    use reponere::build::package_tracker::package_tracker::PackageTracker;
+    use reponere::build::staging::staging_handler::StagingHandler;
+    use reponere::handlers::rollback_handler::{RollbackError, run, select_build};
+    use reponere::util::config::Config;
+    use tempfile::tempdir;
+
+    fn build(version: &str) -> InstalledBuild {
+        InstalledBuild {
+            version: version.to_string(),
+            install_path: String::new(),
+            dependencies: vec![],
+            archive: format!("/history/mypkg/{version}.tar.gz"),
+        }
+    }
+
+    fn tracker_with_history(installed: &str, versions: &[&str]) -> PackageTracker {
+        let mut tracker = PackageTracker::default();
+        tracker.add_package(InstalledPackage {
+            name: "mypkg".to_string(),
+            version: installed.to_string(),
+            install_path: String::new(),
+            dependencies: vec![],
+            manifest: Manifest::default(),
+        });
+        for version in versions {
+            tracker.record_build("mypkg", build(version), 5);
+        }
+        tracker
+    }
+
+    #[test]
+    fn test_select_previous_build() {
+        let tracker = tracker_with_history("3.0.0", &["1.0.0", "2.0.0", "3.0.0"]);
+
+        let selected = select_build(&tracker, "mypkg", None).unwrap();
+        assert_eq!(selected.version, "2.0.0");
+
+        let selected = select_build(&tracker, "mypkg", Some("1.0.0")).unwrap();
+        assert_eq!(selected.version, "1.0.0");
+    }
+
+    #[test]
+    fn test_select_without_history() {
+        let tracker = tracker_with_history("1.0.0", &["1.0.0"]);
+
+        assert!(matches!(
+            select_build(&tracker, "mypkg", None),
+            Err(RollbackError::NoPreviousVersion(_))
+        ));
+        assert!(matches!(
+            select_build(&tracker, "mypkg", Some("0.9.0")),
+            Err(RollbackError::VersionNotInHistory { .. })
+        ));
+        assert!(matches!(
+            select_build(&tracker, "other", None),
+            Err(RollbackError::NotInstalled(_))
+        ));
+    }
+
+    #[test]
+    fn test_rollback_prunes_evicted_archives() {
+        let dir = tempdir().unwrap();
+        let mut config = Config::for_root(dir.path());
+        config.history_limit = 2;
+
+        // Empty archives restore nothing into `/`.
+        let mut tracker = tracker_with_history("3.0.0", &[]);
+        let staging = StagingHandler::new(dir.path()).unwrap();
+        for version in ["1.0.0", "2.0.0", "3.0.0"] {
+            let archive = config.history_path.join(format!("mypkg/{version}.tar.gz"));
+            staging.archive(&archive).unwrap();
+            tracker.record_build(
+                "mypkg",
+                InstalledBuild {
+                    archive: archive.to_string_lossy().to_string(),
+                    ..build(version)
+                },
+                5,
+            );
+        }
+
+        let version = run(&config, &mut tracker, "mypkg", None, false, &mut |_| {}).unwrap();
+        assert_eq!(version, "2.0.0");
+
+        let kept: Vec<_> = tracker
+            .history("mypkg")
+            .iter()
+            .map(|b| &b.version)
+            .collect();
+        assert_eq!(kept, ["3.0.0", "2.0.0"]);
+        assert!(!config.history_path.join("mypkg/1.0.0.tar.gz").exists());
+        assert!(config.history_path.join("mypkg/3.0.0.tar.gz").exists());
+    }
+}
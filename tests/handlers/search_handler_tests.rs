@@ -0,0 +1,95 @@
+mod tests {
+    use reponere::build::package::package::InstalledPackage;
+    use reponere::build::package_tracker::package_tracker::PackageTracker;
+    use reponere::build::registry::registry_handler::Registry;
+    use reponere::handlers::search_handler::{self, SearchError};
+    use tempfile::TempDir;
+
+    use crate::common::{self, recipe, write_recipe};
+
+    fn registry(dir: &TempDir, packages: &[(&str, &str, &[&str])]) -> Registry {
+        for (name, description, keywords) in packages {
+            let recipe = format!(
+                "{}description: \"{description}\"\nkeywords: {keywords:?}\n",
+                recipe(name, "1.0.0", &[])
+            );
+            write_recipe(dir.path(), name, "1.0.0", &recipe);
+        }
+        common::registry(dir.path())
+    }
+
+    fn names(reg: &Registry, tracker: &PackageTracker, query: &str, regex: bool) -> Vec<String> {
+        search_handler::run(reg, tracker, query, regex, &mut |_| {})
+            .unwrap()
+            .into_iter()
+            .map(|r| r.name)
+            .collect()
+    }
+
+    fn sample(dir: &TempDir) -> Registry {
+        registry(
+            dir,
+            &[
+                (
+                    "ripgrep",
+                    "recursively search directories",
+                    &["grep", "search"],
+                ),
+                ("grep-tools", "helpers", &[]),
+                ("fd", "find entries in the filesystem", &["find"]),
+                ("neovim", "hyperextensible editor", &["editor", "vim"]),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_search_ranks_name_over_keyword_over_description() {
+        let dir = TempDir::new().unwrap();
+        let reg = sample(&dir);
+        let tracker = PackageTracker::default();
+
+        assert_eq!(
+            names(&reg, &tracker, "grep", false),
+            ["grep-tools", "ripgrep"]
+        );
+        assert_eq!(names(&reg, &tracker, "search", false), ["ripgrep"]);
+        assert_eq!(names(&reg, &tracker, "FILESYSTEM", false), ["fd"]);
+        // fuzzy subsequence of the name
+        assert_eq!(names(&reg, &tracker, "nvm", false), ["neovim"]);
+        assert!(names(&reg, &tracker, "emacs", false).is_empty());
+    }
+
+    #[test]
+    fn test_search_regex() {
+        let dir = TempDir::new().unwrap();
+        let reg = sample(&dir);
+        let tracker = PackageTracker::default();
+
+        assert_eq!(names(&reg, &tracker, "^(fd|neo)", true), ["fd", "neovim"]);
+        assert_eq!(names(&reg, &tracker, "^vim$", true), ["neovim"]);
+        assert!(matches!(
+            search_handler::run(&reg, &tracker, "(", true, &mut |_| {}),
+            Err(SearchError::InvalidRegex(_))
+        ));
+    }
+
+    #[test]
+    fn test_search_marks_installed_packages() {
+        let dir = TempDir::new().unwrap();
+        let reg = sample(&dir);
+        let mut tracker = PackageTracker::default();
+        tracker.add_package(InstalledPackage {
+            name: "fd".to_string(),
+            version: "0.9.0".to_string(),
+            ..Default::default()
+        });
+
+        let results = search_handler::run(&reg, &tracker, "f", false, &mut |_| {}).unwrap();
+        let fd = results.iter().find(|r| r.name == "fd").unwrap();
+        assert_eq!(fd.installed.as_deref(), Some("0.9.0"));
+        assert_eq!(
+            fd.description.as_deref(),
+            Some("find entries in the filesystem")
+        );
+    }
+}
@@ -0,0 +1,79 @@
+mod tests {
+    use git2::{IndexAddOption, Repository, Signature};
+    use reponere::build::registry::remote::{RemoteRegistry, RemoteSource};
+    use reponere::handlers::{events::SyncEvent, sync_handler};
+    use reponere::util::config::{Config, ConfigError};
+    use tempfile::tempdir;
+
+    use crate::common::write_release;
+
+    #[test]
+    fn test_sync_warns_about_unsigned_remotes() {
+        let upstream = tempdir().unwrap();
+        let repo = Repository::init(upstream.path()).unwrap();
+        write_release(upstream.path(), "hello", "1.0.0");
+        let mut index = repo.index().unwrap();
+        index.add_all(["*"], IndexAddOption::DEFAULT, None).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("test", "test@example.com").unwrap();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "add hello",
+            &tree,
+            &[],
+        )
+        .unwrap();
+
+        let dir = tempdir().unwrap();
+        let mut config = Config::for_root(dir.path());
+        config.registries.push(RemoteRegistry {
+            name: "main".to_string(),
+            priority: 0,
+            allow_unsigned: true,
+            source: RemoteSource::Git {
+                url: upstream.path().to_str().unwrap().to_string(),
+                branch: None,
+            },
+        });
+
+        let mut warned = Vec::new();
+        let registry = sync_handler::run(&config, &mut |event| {
+            if let SyncEvent::UnsignedRemote { name } = event {
+                warned.push(name);
+            }
+        })
+        .unwrap();
+
+        assert_eq!(warned, ["main"]);
+        assert!(registry.get_package("hello").is_some());
+    }
+
+    #[test]
+    fn test_sync_rejects_invalid_registries_and_pins() {
+        let dir = tempdir().unwrap();
+        let mut config = Config::for_root(dir.path());
+        config
+            .pins
+            .insert("tool".to_string(), "internal".to_string());
+
+        assert!(matches!(
+            sync_handler::run(&config, &mut |_| {}),
+            Err(ConfigError::UnknownPinRegistry { .. })
+        ));
+
+        config.registries.push(RemoteRegistry {
+            name: "../internal".to_string(),
+            priority: 0,
+            allow_unsigned: false,
+            source: RemoteSource::Http {
+                url: "https://example.com".to_string(),
+            },
+        });
+        assert!(matches!(
+            sync_handler::run(&config, &mut |_| {}),
+            Err(ConfigError::InvalidRegistry { .. })
+        ));
+    }
+}
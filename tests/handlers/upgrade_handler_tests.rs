@@ -0,0 +1,62 @@
+mod tests {
+    use reponere::build::package::package::{InstalledPackage, Manifest};
+    use reponere::build::package_tracker::package_tracker::PackageTracker;
+    use reponere::build::registry::registry_handler::Registry;
+    use reponere::handlers::upgrade_handler::{self, UpgradeCandidate, UpgradeError};
+    use tempfile::TempDir;
+
+    use crate::common::{self, write_release};
+
+    fn registry(dir: &TempDir, releases: &[(&str, &str)]) -> Registry {
+        for (name, version) in releases {
+            write_release(dir.path(), name, version);
+        }
+        common::registry(dir.path())
+    }
+
+    fn installed(name: &str, version: &str) -> InstalledPackage {
+        InstalledPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            install_path: String::new(),
+            dependencies: vec![],
+            manifest: Manifest::default(),
+        }
+    }
+
+    #[test]
+    fn test_plan_selects_outdated_packages() {
+        let dir = TempDir::new().unwrap();
+        let reg = registry(
+            &dir,
+            &[("old", "1.0.0"), ("old", "2.0.0"), ("fresh", "1.0.0")],
+        );
+
+        let mut tracker = PackageTracker::default();
+        tracker.add_package(installed("old", "1.0.0"));
+        tracker.add_package(installed("fresh", "1.0.0"));
+        tracker.add_package(installed("unlisted", "1.0.0"));
+
+        let plan = upgrade_handler::plan(&reg, &tracker, &[]).unwrap();
+        assert_eq!(
+            plan,
+            vec![UpgradeCandidate {
+                name: "old".to_string(),
+                current: "1.0.0".to_string(),
+                latest: "2.0.0".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_plan_rejects_packages_not_installed() {
+        let dir = TempDir::new().unwrap();
+        let reg = registry(&dir, &[("pkg", "1.0.0")]);
+        let tracker = PackageTracker::default();
+
+        assert!(matches!(
+            upgrade_handler::plan(&reg, &tracker, &["pkg".to_string()]),
+            Err(UpgradeError::NotInstalled(_))
+        ));
+    }
+}
@@ -1 +1,3 @@
 mod build;
+mod common;
+mod handlers;